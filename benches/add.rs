@@ -0,0 +1,125 @@
+#![allow(unused_imports)]
+#![allow(unused_variables)]
+#![allow(dead_code)]
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use halo2_proofs::{
+  arithmetic::CurveAffine,
+  circuit::{Chip, SimpleFloorPlanner, Value},
+  pasta::{
+    group::ff::{Field, FromUniformBytes, PrimeField},
+    pallas, vesta, Fp, Fq,
+  },
+  plonk::{
+    create_proof, keygen_pk, keygen_vk, verify_proof, Advice, Circuit, Column, ConstraintSystem,
+    Fixed, Instance, ProvingKey, SingleVerifier, VerifyingKey,
+  },
+  poly::commitment::Params,
+  transcript::{Blake2bRead, Blake2bWrite, Challenge255, Transcript},
+};
+use halo_2_benches::{
+  gadgets::scalar_mul::*,
+  prover::{prove, setup, verify},
+  Curve, Scalar,
+};
+
+/// returning a+b
+#[derive(Default, Clone)]
+pub struct AddCircuit<F: Field> {
+  pub a: Value<F>,
+  pub b: Value<F>,
+}
+
+impl<F: PrimeField> Circuit<F> for AddCircuit<F> {
+  // the chip needs to be configured
+  // field choice for the Circuit, see below
+  // can have Circuit config overlap with Chip config since only one Chip
+  type Config = ScalarMulConfig;
+  // algorithm to plan table layout, using the default here
+  type FloorPlanner = SimpleFloorPlanner;
+
+  // typically just default
+  fn without_witnesses(&self) -> Self { Self::default() }
+
+  // describe exact gate/column arrangement
+  fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+    // used for IO; the widest gate (mul_add) has fan-in 3, so need 3 advice cols
+    let advice = [meta.advice_column(), meta.advice_column(), meta.advice_column()];
+    // store public inputs in Instance columns
+    let instance = meta.instance_column();
+    // for loading a constant
+    let constant = meta.fixed_column();
+    // return the column configuration
+    Self::Config::configure(meta, &advice, &[instance], constant)
+  }
+
+  // Create the circuit WRT the constraint system
+  fn synthesize(
+    &self,
+    config: Self::Config,
+    mut layouter: impl halo2_proofs::circuit::Layouter<F>,
+  ) -> Result<(), halo2_proofs::plonk::Error> {
+    // load any used arithmetic chips; see below for the construction of our chip
+    let field_chip = ScalarMulChip::<F>::new(config);
+
+    // Load {private, constant} values into the circuit
+    let a = field_chip.load_private(layouter.namespace(|| "load a"), self.a)?;
+    let b = field_chip.load_private(layouter.namespace(|| "load b"), self.b)?;
+    // Finally, tell the circuit how to use our Chip
+    let c = field_chip.add(layouter.namespace(|| "a + b"), a, b)?;
+
+    // and "return" the result as a public input to the circuit
+    field_chip.expose_public(layouter.namespace(|| "expose result"), c, 0)
+  }
+}
+
+pub struct Workbench {
+  name:            String,
+  params:          Params<Curve>,
+  pk:              ProvingKey<Curve>,
+  vk:              VerifyingKey<Curve>,
+  circuit:         AddCircuit<Scalar>,
+  expected_output: Scalar,
+}
+
+pub fn workbench() -> Workbench {
+  // ANCHOR: test-circuit
+  // 2^k is the number of rows in our circuit
+  let k = 4;
+  // Instantiate the circuit with the private inputs.
+  let (circuit, expected_output) = {
+    let a = Scalar::from(2);
+    let b = Scalar::from(3);
+    let c = a + b;
+    let (a, b) = (Value::known(a), Value::known(b));
+    (AddCircuit { a, b }, c)
+  };
+
+  // Initialize the proving key
+  let (params, pk, vk) = setup(k, &circuit);
+
+  Workbench { name: String::from("add"), params, pk, vk, circuit, expected_output }
+}
+
+pub fn bench_add(w: Workbench, crit: &mut Criterion) {
+  let Workbench { params, pk, vk, circuit, expected_output, name } = w;
+  let prover_str = format!("{}-prover", name);
+  let verifier_str = format!("{}-verifier", name);
+
+  crit.bench_function(&prover_str, |b| {
+    b.iter(|| prove(&params, &pk, circuit.clone(), &[&[expected_output]]))
+  });
+
+  let proof = prove(&params, &pk, circuit, &[&[expected_output]]);
+
+  crit.bench_function(&verifier_str, |b| {
+    b.iter(|| {
+      assert!(verify(&params, &vk, &[&[expected_output]], &proof).is_ok());
+    });
+  });
+}
+
+fn run_bench(c: &mut Criterion) { bench_add(workbench(), c); }
+
+criterion_group!(benches, run_bench);
+criterion_main!(benches);
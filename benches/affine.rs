@@ -0,0 +1,74 @@
+#![allow(unused_imports)]
+#![allow(unused_variables)]
+#![allow(dead_code)]
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use halo2_proofs::{
+  pasta::{pallas, vesta, Fp, Fq},
+  plonk::{ProvingKey, VerifyingKey},
+  poly::commitment::Params,
+};
+use halo_2_benches::{
+  circuits::affine::AffineCircuit,
+  prover::{prove, setup, verify},
+  Curve, Scalar,
+};
+
+pub struct Workbench {
+  name:            String,
+  params:          Params<Curve>,
+  pk:              ProvingKey<Curve>,
+  vk:              VerifyingKey<Curve>,
+  circuit:         AffineCircuit<Scalar>,
+  expected_output: Scalar,
+}
+
+/// a neuron over `dim` inputs, over 2^k rows
+pub fn workbench(dim: usize, k: u32) -> Workbench {
+  // Instantiate the circuit with the private inputs.
+  let inputs: Vec<Scalar> = (0..dim).map(|i| Scalar::from(i as u64)).collect();
+  let weights: Vec<Scalar> = (0..dim).map(|i| Scalar::from(i as u64 + 1)).collect();
+  let (circuit, expected_output) = AffineCircuit::new(&inputs, &weights, Scalar::from(7));
+
+  let (params, pk, vk) = setup(k, &circuit);
+
+  Workbench { name: format!("dim={dim}"), params, pk, vk, circuit, expected_output }
+}
+
+/// bench each input dimension in one group per prover/verifier
+pub fn bench_affine(workbenches: Vec<Workbench>, crit: &mut Criterion) {
+  let mut group = crit.benchmark_group("affine");
+  group.sample_size(10);
+  for w in &workbenches {
+    let Workbench { name, params, pk, circuit, expected_output, .. } = w;
+    group.throughput(Throughput::Elements(circuit.gate_count()));
+    group.bench_function(name, |b| {
+      b.iter(|| prove(params, pk, circuit.clone(), &[&[*expected_output]]))
+    });
+  }
+  group.finish();
+
+  let mut group = crit.benchmark_group("affine-verifier");
+  for w in workbenches {
+    let Workbench { name, params, pk, vk, circuit, expected_output } = w;
+
+    let proof = prove(&params, &pk, circuit, &[&[expected_output]]);
+
+    group.bench_function(name, |b| {
+      b.iter(|| {
+        assert!(verify(&params, &vk, &[&[expected_output]], &proof).is_ok());
+      });
+    });
+  }
+  group.finish();
+}
+
+fn run_bench(c: &mut Criterion) {
+  // two load rows and two dot rows per input
+  let workbenches =
+    [(4, 5), (16, 7), (64, 9), (256, 11)].into_iter().map(|(dim, k)| workbench(dim, k)).collect();
+  bench_affine(workbenches, c);
+}
+
+criterion_group!(benches, run_bench);
+criterion_main!(benches);
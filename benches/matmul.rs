@@ -0,0 +1,75 @@
+#![allow(unused_imports)]
+#![allow(unused_variables)]
+#![allow(dead_code)]
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use halo2_proofs::{
+  pasta::{pallas, vesta, Fp, Fq},
+  plonk::{ProvingKey, VerifyingKey},
+  poly::commitment::Params,
+};
+use halo_2_benches::{
+  circuits::matmul::MatMulCircuit,
+  prover::{prove, setup, verify},
+  Curve, Scalar,
+};
+
+pub struct Workbench {
+  name:            String,
+  params:          Params<Curve>,
+  pk:              ProvingKey<Curve>,
+  vk:              VerifyingKey<Curve>,
+  circuit:         MatMulCircuit<Scalar>,
+  expected_output: Vec<Scalar>,
+}
+
+/// square `dim` x `dim` matrices, over 2^k rows
+pub fn workbench(dim: usize, k: u32) -> Workbench {
+  // Instantiate the circuit with the private inputs.
+  let a: Vec<Scalar> = (0..dim * dim).map(|i| Scalar::from(i as u64)).collect();
+  let b: Vec<Scalar> = (0..dim * dim).map(|i| Scalar::from(i as u64 + 1)).collect();
+  let (circuit, expected_output) = MatMulCircuit::new(&a, &b, (dim, dim, dim));
+
+  let (params, pk, vk) = setup(k, &circuit);
+
+  Workbench { name: format!("{dim}x{dim}"), params, pk, vk, circuit, expected_output }
+}
+
+/// bench each dimension in one group per prover/verifier, to plot the scaling in layer size
+pub fn bench_matmul(workbenches: Vec<Workbench>, crit: &mut Criterion) {
+  let mut group = crit.benchmark_group("matmul");
+  group.sample_size(10);
+  for w in &workbenches {
+    let Workbench { name, params, pk, circuit, expected_output, .. } = w;
+    // report multiplications per second, comparable across dimensions
+    group.throughput(Throughput::Elements(circuit.gate_count()));
+    group.bench_function(name, |b| {
+      b.iter(|| prove(params, pk, circuit.clone(), &[&expected_output[..]]))
+    });
+  }
+  group.finish();
+
+  let mut group = crit.benchmark_group("matmul-verifier");
+  for w in workbenches {
+    let Workbench { name, params, pk, vk, circuit, expected_output } = w;
+
+    let proof = prove(&params, &pk, circuit, &[&expected_output[..]]);
+
+    group.bench_function(name, |b| {
+      b.iter(|| {
+        assert!(verify(&params, &vk, &[&expected_output[..]], &proof).is_ok());
+      });
+    });
+  }
+  group.finish();
+}
+
+fn run_bench(c: &mut Criterion) {
+  // dim^3 multiplications for dim x dim matrices, so k has to grow with dim
+  let workbenches =
+    [(2, 6), (4, 9), (8, 12)].into_iter().map(|(dim, k)| workbench(dim, k)).collect();
+  bench_matmul(workbenches, c);
+}
+
+criterion_group!(benches, run_bench);
+criterion_main!(benches);
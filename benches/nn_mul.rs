@@ -2,150 +2,240 @@
 #![allow(unused_variables)]
 #![allow(dead_code)]
 
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use criterion::{
+  black_box, criterion_group, criterion_main, measurement::WallTime, BenchmarkGroup, Criterion,
+  Throughput,
+};
 use halo2_proofs::{
   arithmetic::CurveAffine,
   circuit::{Chip, SimpleFloorPlanner, Value},
+  dev::MockProver,
   pasta::{
-    group::ff::{Field, FromUniformBytes},
-    vesta, Fp,
+    group::ff::{Field, FromUniformBytes, PrimeField},
+    pallas, vesta, Fp, Fq,
   },
   plonk::{
-    create_proof, keygen_pk, keygen_vk, Advice, Circuit, Column, ConstraintSystem, Fixed, Instance,
-    ProvingKey, VerifyingKey,
+    create_proof, keygen_pk, keygen_vk, verify_proof, Advice, Circuit, Column, ConstraintSystem,
+    Fixed, Instance, ProvingKey, SingleVerifier, VerifyingKey,
   },
   poly::commitment::Params,
-  transcript::{Challenge255, Transcript},
+  transcript::{Blake2bRead, Blake2bWrite, Challenge255, Transcript},
+};
+use halo_2_benches::{
+  circuits::{
+    iterated_mul::IteratedMulCircuit,
+    mul_layout::{Layout, Packed, ThreeColSameRow, TwoColNextRow, WithMulLayout},
+    nn_mul::{proof_size, NNMulCircuit},
+  },
+  cost::describe_cost,
+  gadgets::scalar_mul::*,
+  prover::{prove, prove_parallel, prove_with_transcript, setup, verify, verify_with_transcript},
+  Curve, Scalar,
 };
-use halo_2_benches::gadgets::scalar_mul::*;
 
-type VestaAffine = vesta::Affine;
+pub struct Workbench {
+  name:            String,
+  params:          Params<Curve>,
+  pk:              ProvingKey<Curve>,
+  vk:              VerifyingKey<Curve>,
+  circuit:         NNMulCircuit<Scalar>,
+  expected_output: Scalar,
+}
+
+/// `name` prefixes the criterion ids, so bench files don't overwrite each other's baselines; 2^k
+/// is the number of rows in our circuit
+pub fn workbench(name: &str, k: u32) -> Workbench {
+  // Instantiate the circuit with the private inputs.
+  let (circuit, expected_output) = NNMulCircuit::new(Scalar::from(2), Scalar::from(3));
+  let (params, pk, vk) = setup(k, &circuit);
+
+  Workbench { name: name.to_string(), params, pk, vk, circuit, expected_output }
+}
+
+pub fn bench_scalar_mul(w: Workbench, crit: &mut Criterion) {
+  let Workbench { params, pk, vk, circuit, expected_output, name } = w;
+
+  // report multiplications per second, comparable across circuits of different sizes
+  let mut group = crit.benchmark_group(&name);
+  group.throughput(Throughput::Elements(circuit.gate_count()));
+
+  group.bench_function("prover", |b| {
+    b.iter(|| prove(&params, &pk, circuit.clone(), &[&[expected_output]]))
+  });
 
-/// returning a*b
-#[derive(Default, Clone)]
-pub struct NNMulCircuit<F: Field> {
-  pub a: Value<F>,
-  pub b: Value<F>,
+  let proof = prove(&params, &pk, circuit, &[&[expected_output]]);
+
+  group.bench_function("verifier", |b| {
+    b.iter(|| {
+      assert!(verify(&params, &vk, &[&[expected_output]], &proof).is_ok());
+    });
+  });
+  group.finish();
 }
 
-impl<F: Field> Circuit<F> for NNMulCircuit<F> {
-  // the chip needs to be configured
-  // field choice for the Circuit, see below
-  // can have Circuit config overlap with Chip config since only one Chip
-  type Config = ScalarMulConfig;
-  // algorithm to plan table layout, using the default here
-  type FloorPlanner = SimpleFloorPlanner;
-
-  // typically just default
-  fn without_witnesses(&self) -> Self { Self::default() }
-
-  // describe exact gate/column arrangement
-  fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
-    // used for IO; have a fan-in 2 circuit gate, so need 2 advice cols
-    let advice = [meta.advice_column(), meta.advice_column()];
-    // store public inputs in Instance columns
-    let instance = meta.instance_column();
-    // for loading a constant
-    let constant = meta.fixed_column();
-    // return the column configuration
-    Self::Config::configure(meta, advice, instance, constant)
+/// bench the same three muls laid out in one region, against `NNMulCircuit`'s region per mul
+pub fn bench_iterated_mul(crit: &mut Criterion) {
+  let k = 4;
+  let (circuit, expected_output) = IteratedMulCircuit::new(Scalar::from(2), 3);
+  let (params, pk, vk) = setup::<Curve, _>(k, &circuit);
+
+  let mut group = crit.benchmark_group("iterated_mul");
+  group.throughput(Throughput::Elements(circuit.gate_count()));
+
+  group.bench_function("prover", |b| {
+    b.iter(|| prove(&params, &pk, circuit.clone(), &[&[expected_output]]))
+  });
+
+  let proof = prove(&params, &pk, circuit, &[&[expected_output]]);
+
+  group.bench_function("verifier", |b| {
+    b.iter(|| {
+      assert!(verify(&params, &vk, &[&[expected_output]], &proof).is_ok());
+    });
+  });
+  group.finish();
+}
+
+/// bench proving a chain of muls under the `MulLayout` marked by `L`, at fixed k
+fn bench_layout<L: Layout>(name: &str, group: &mut BenchmarkGroup<WallTime>) {
+  let k = 5;
+  let (circuit, expected_output) = IteratedMulCircuit::new(Scalar::from(2), 8);
+  let circuit = WithMulLayout::<_, L>::new(circuit);
+  let (params, pk, _) = setup::<Curve, _>(k, &circuit);
+
+  // criterion only measures time, so report the layout's columns, selectors, gates and rows
+  // alongside; the cost is read off the constraint system, so an Fp circuit stands in for Scalar
+  let (cost_circuit, _) = IteratedMulCircuit::new(Fp::from(2), 8);
+  let cost = describe_cost(k, &WithMulLayout::<_, L>::new(cost_circuit));
+  println!("mul_layout {name}: {cost}");
+  group.bench_function(name, |b| {
+    b.iter(|| prove(&params, &pk, circuit.clone(), &[&[expected_output]]))
+  });
+}
+
+/// bench proving under each `MulLayout`, to pick the default
+pub fn bench_mul_layout(crit: &mut Criterion) {
+  let mut group = crit.benchmark_group("mul_layout");
+  bench_layout::<TwoColNextRow>("two_col_next_row", &mut group);
+  bench_layout::<ThreeColSameRow>("three_col_same_row", &mut group);
+  bench_layout::<Packed>("packed", &mut group);
+  group.finish();
+}
+
+/// bench proving 8 independent instances one after another, against in parallel
+pub fn bench_parallel(w: &Workbench, crit: &mut Criterion) {
+  let Workbench { params, pk, circuit, expected_output, .. } = w;
+  let circuits = vec![circuit.clone(); 8];
+  let public_inputs = [&[*expected_output][..]];
+  let public_inputs = vec![&public_inputs[..]; 8];
+
+  let mut group = crit.benchmark_group("nn_mul-x8");
+  group.sample_size(10);
+  group.bench_function("serial", |b| {
+    b.iter(|| {
+      circuits
+        .iter()
+        .zip(&public_inputs)
+        .map(|(circuit, public_inputs)| prove(params, pk, circuit.clone(), *public_inputs))
+        .collect::<Vec<_>>()
+    })
+  });
+  group.bench_function("parallel", |b| {
+    b.iter(|| prove_parallel(params, pk, &circuits, &public_inputs))
+  });
+  group.finish();
+}
+
+/// bench checking the circuit with the `MockProver` against proving and verifying it for real, to
+/// show that the mock's cost says little about a real proof's; keygen is left out of both
+pub fn bench_mock_vs_real(crit: &mut Criterion) {
+  let (circuit, expected_output) = NNMulCircuit::new(Scalar::from(2), Scalar::from(3));
+
+  let mut group = crit.benchmark_group("mock_vs_real");
+  group.sample_size(10);
+  for k in [4, 8, 12] {
+    group.bench_function(format!("mock/k={k}"), |b| {
+      b.iter(|| {
+        let prover = MockProver::run(k, &circuit, vec![vec![expected_output]])
+          .expect("synthesis should not fail");
+        assert_eq!(prover.verify(), Ok(()));
+      })
+    });
+
+    let (params, pk, vk) = setup::<Curve, _>(k, &circuit);
+    group.bench_function(format!("real/k={k}"), |b| {
+      b.iter(|| {
+        let proof = prove(&params, &pk, circuit.clone(), &[&[expected_output]]);
+        assert!(verify(&params, &vk, &[&[expected_output]], &proof).is_ok());
+      })
+    });
   }
+  group.finish();
+}
 
-  // Create the circuit WRT the constraint system
-  fn synthesize(
-    &self,
-    config: Self::Config,
-    mut layouter: impl halo2_proofs::circuit::Layouter<F>,
-  ) -> Result<(), halo2_proofs::plonk::Error> {
-    // load any used arithmetic chips; see below for the construction of our chip
-    let field_chip = ScalarMulChip::<F>::new(config);
-
-    // Load {private, constant} values into the circuit
-    let a = field_chip.load_private(layouter.namespace(|| "load a"), self.a)?;
-    let b = field_chip.load_private(layouter.namespace(|| "load b"), self.b)?;
-    // Finally, tell the circuit how to use our Chip
-    let c = field_chip.mul(layouter.namespace(|| "a * b"), a.clone(), a)?;
-
-    // and "return" the result as a public input to the circuit
-    field_chip.expose_public(layouter.namespace(|| "expose result"), c, 0)
+/// bench setup separately from proving, to tell one-time cost from per-proof cost
+pub fn bench_keygen(crit: &mut Criterion) {
+  let (circuit, _) = NNMulCircuit::new(Scalar::from(2), Scalar::from(3));
+
+  let mut group = crit.benchmark_group("keygen");
+  group.sample_size(10);
+  for k in [4, 8, 12] {
+    let params: Params<Curve> = Params::new(k);
+    group.bench_function(format!("vk/k={k}"), |b| {
+      b.iter(|| keygen_vk(&params, &circuit).expect("keygen_vk should not fail"))
+    });
+
+    // keygen_pk consumes the vk, so the measurement includes a (cheap) clone of it
+    let vk = keygen_vk(&params, &circuit).expect("keygen_vk should not fail");
+    group.bench_function(format!("pk/k={k}"), |b| {
+      b.iter(|| keygen_pk(&params, vk.clone(), &circuit).expect("keygen_pk should not fail"))
+    });
   }
+  group.finish();
 }
 
-pub struct Workbench {
-  name:            String,
-  params:          Params<VestaAffine>,
-  pk:              ProvingKey<VestaAffine>,
-  vk:              VerifyingKey<VestaAffine>,
-  circuit:         NNMulCircuit<Fp>,
-  expected_output: Fp,
-  rng:             rand::rngs::OsRng,
+/// bench the same circuit under each transcript hash, for the cost of Fiat-Shamir alone
+pub fn bench_transcript(w: &Workbench, crit: &mut Criterion) {
+  let Workbench { params, pk, vk, circuit, expected_output, .. } = w;
+
+  let mut group = crit.benchmark_group("transcript");
+  // halo2_proofs 0.3 only ships Blake2b; add an entry per `TranscriptWrite` impl as they appear
+  group.bench_function("blake2b-prover", |b| {
+    b.iter(|| {
+      let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+      prove_with_transcript(params, pk, circuit.clone(), &[&[*expected_output]], &mut transcript);
+      transcript.finalize()
+    })
+  });
+
+  let proof = prove(params, pk, circuit.clone(), &[&[*expected_output]]);
+  group.bench_function("blake2b-verifier", |b| {
+    b.iter(|| {
+      let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
+      assert!(verify_with_transcript(params, vk, &[&[*expected_output]], &mut transcript).is_ok());
+    })
+  });
+  group.finish();
 }
 
-pub fn workbench() -> Workbench {
-  // ANCHOR: test-circuit
-  // 2^k is the number of rows in our circuit
-  let k = 4;
-  // Instantiate the circuit with the private inputs.
-  let (circuit, expected_output) = {
-    let a = Fp::from(2);
-    let b = Fp::from(3);
-    let c = a.square() * b.square();
-    let (a, b) = (Value::known(a), Value::known(b));
-    (NNMulCircuit { a, b }, c)
-  };
-
-  // Initialize the proving key
-  let params = Params::new(k);
-  let vk = keygen_vk(&params, &circuit).expect("keygen_vk should not fail");
-  let pk = keygen_pk(&params, vk.clone(), &circuit).expect("keygen_pk should not fail");
-
-  // Arrange the public input. We expose the multiplication result in row 0
-  // of the instance column, so we position it there in our public inputs.
-  // let mut public_inputs = vec![c];
-
-  // // Given the correct public input, our circuit will verify.
-  // let prover = MockProver::run(k, &my_circuit, vec![public_inputs.clone()]).unwrap();
-  // assert_eq!(prover.verify(), Ok(()));
-
-  Workbench {
-    name: String::from("scalar_mul"),
-    params,
-    pk,
-    vk,
-    circuit,
-    expected_output,
-    rng: rand::rngs::OsRng,
+/// criterion only measures time, so report proof size, the other metric for picking k, alongside
+pub fn report_proof_sizes() {
+  for k in [4, 8, 12] {
+    println!("nn_mul proof size at k={k}: {} bytes", proof_size(k));
   }
 }
 
-pub fn bench_scalar_mul(w: Workbench, crit: &mut Criterion) {
-//   let Workbench { params, pk, vk, circuit, expected_output, mut rng, name } = w;
-
-//   let prover_str = format!("{}-prover", name);
-//   let verifier_str = format!("{}-verifier", name);
-
-//   crit.bench_function(&prover_str, |b| {
-//     b.iter(|| {
-//       // ref: https://github.com/zcash/halo2/blob/76b3f892a9d598923bbb5a747701fff44ae4c0ea/halo2_gadgets/benches/poseidon.rs#L178
-//       // choose a hash function for FS challenges
-//       // Why blake2b not poseidon?
-//       // > We will replace BLAKE2b with an algebraic hash function in a later version. - Halo 2 authors
-//       let mut transcript =
-//         halo2_proofs::transcript::Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
-//       create_proof(
-//         &params,
-//         &pk,
-//         &[circuit.clone()],
-//         &[&[&[expected_output]]],
-//         &mut rng,
-//         &mut transcript,
-//       )
-//       .unwrap();
-//     })
-//   });
+fn run_bench(c: &mut Criterion) {
+  report_proof_sizes();
+  let w = workbench("nn_mul", 4);
+  bench_transcript(&w, c);
+  bench_parallel(&w, c);
+  bench_scalar_mul(w, c);
+  bench_iterated_mul(c);
+  bench_mul_layout(c);
+  bench_keygen(c);
+  bench_mock_vs_real(c);
 }
 
-fn run_bench(c: &mut Criterion) { bench_scalar_mul(workbench(), c); }
-
 criterion_group!(benches, run_bench);
 criterion_main!(benches);
@@ -0,0 +1,70 @@
+#![allow(unused_imports)]
+#![allow(unused_variables)]
+#![allow(dead_code)]
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use halo2_proofs::{
+  pasta::{pallas, vesta, Fp, Fq},
+  plonk::{ProvingKey, VerifyingKey},
+  poly::commitment::Params,
+};
+use halo_2_benches::{
+  circuits::poly::PolyCircuit,
+  prover::{prove, setup, verify},
+  Curve, Scalar,
+};
+
+pub struct Workbench {
+  name:            String,
+  params:          Params<Curve>,
+  pk:              ProvingKey<Curve>,
+  vk:              VerifyingKey<Curve>,
+  circuit:         PolyCircuit<Scalar>,
+  expected_output: Scalar,
+}
+
+/// a chain of `depth` muls over 2^k rows, built with `mul_chained` if `chained`
+pub fn workbench(name: &str, k: u32, depth: usize, chained: bool) -> Workbench {
+  let (mut circuit, expected_output) = PolyCircuit::new(Scalar::from(3), depth);
+  circuit.chained = chained;
+
+  let (params, pk, vk) = setup(k, &circuit);
+
+  Workbench { name: name.to_string(), params, pk, vk, circuit, expected_output }
+}
+
+/// bench chained against naive muls in one group per prover/verifier
+pub fn bench_poly(workbenches: Vec<Workbench>, crit: &mut Criterion) {
+  let mut group = crit.benchmark_group("poly");
+  for w in &workbenches {
+    let Workbench { name, params, pk, circuit, expected_output, .. } = w;
+    group.throughput(Throughput::Elements(circuit.gate_count()));
+    group.bench_function(name, |b| {
+      b.iter(|| prove(params, pk, circuit.clone(), &[&[*expected_output]]))
+    });
+  }
+  group.finish();
+
+  let mut group = crit.benchmark_group("poly-verifier");
+  for w in workbenches {
+    let Workbench { name, params, pk, vk, circuit, expected_output } = w;
+
+    let proof = prove(&params, &pk, circuit, &[&[expected_output]]);
+
+    group.bench_function(name, |b| {
+      b.iter(|| {
+        assert!(verify(&params, &vk, &[&[expected_output]], &proof).is_ok());
+      });
+    });
+  }
+  group.finish();
+}
+
+fn run_bench(c: &mut Criterion) {
+  let workbenches =
+    vec![workbench("naive/depth=10", 6, 10, false), workbench("chained/depth=10", 6, 10, true)];
+  bench_poly(workbenches, c);
+}
+
+criterion_group!(benches, run_bench);
+criterion_main!(benches);
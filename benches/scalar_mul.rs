@@ -7,28 +7,32 @@ use halo2_proofs::{
   arithmetic::CurveAffine,
   circuit::{Chip, SimpleFloorPlanner, Value},
   pasta::{
-    group::ff::{Field, FromUniformBytes},
-    vesta, Fp,
+    group::ff::{Field, FromUniformBytes, PrimeField},
+    pallas, vesta, Fp, Fq,
   },
   plonk::{
-    create_proof, keygen_pk, keygen_vk, Advice, Circuit, Column, ConstraintSystem, Fixed, Instance,
-    ProvingKey, VerifyingKey, SingleVerifier, verify_proof,
+    create_proof, keygen_pk, keygen_vk, verify_proof, Advice, Circuit, Column, ConstraintSystem,
+    Fixed, Instance, ProvingKey, SingleVerifier, VerifyingKey,
   },
   poly::commitment::Params,
-  transcript::{Blake2bWrite, Challenge255, Transcript, Blake2bRead},
+  transcript::{Blake2bRead, Blake2bWrite, Challenge255, Transcript},
+};
+use halo_2_benches::{
+  gadgets::scalar_mul::*,
+  prover::{prove, setup, verify},
+  Curve, Scalar,
 };
-use halo_2_benches::gadgets::scalar_mul::*;
-
-type VestaAffine = vesta::Affine;
 
 /// returning a*b
 #[derive(Default, Clone)]
 pub struct ScalarMulCircuit<F: Field> {
-  pub a: Value<F>,
-  pub b: Value<F>,
+  pub a:      Value<F>,
+  pub b:      Value<F>,
+  /// use `mul_inline` instead of loading the inputs and copying them into `mul`
+  pub inline: bool,
 }
 
-impl<F: Field> Circuit<F> for ScalarMulCircuit<F> {
+impl<F: PrimeField> Circuit<F> for ScalarMulCircuit<F> {
   // the chip needs to be configured
   // field choice for the Circuit, see below
   // can have Circuit config overlap with Chip config since only one Chip
@@ -36,19 +40,19 @@ impl<F: Field> Circuit<F> for ScalarMulCircuit<F> {
   // algorithm to plan table layout, using the default here
   type FloorPlanner = SimpleFloorPlanner;
 
-  // typically just default
-  fn without_witnesses(&self) -> Self { Self::default() }
+  // keep the layout choice, since keygen synthesizes this circuit
+  fn without_witnesses(&self) -> Self { Self { inline: self.inline, ..Self::default() } }
 
   // describe exact gate/column arrangement
   fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
-    // used for IO; have a fan-in 2 circuit gate, so need 2 advice cols
-    let advice = [meta.advice_column(), meta.advice_column()];
+    // used for IO; the widest gate (mul_add) has fan-in 3, so need 3 advice cols
+    let advice = [meta.advice_column(), meta.advice_column(), meta.advice_column()];
     // store public inputs in Instance columns
     let instance = meta.instance_column();
     // for loading a constant
     let constant = meta.fixed_column();
     // return the column configuration
-    Self::Config::configure(meta, advice, instance, constant)
+    Self::Config::configure(meta, &advice, &[instance], constant)
   }
 
   // Create the circuit WRT the constraint system
@@ -60,11 +64,16 @@ impl<F: Field> Circuit<F> for ScalarMulCircuit<F> {
     // load any used arithmetic chips; see below for the construction of our chip
     let field_chip = ScalarMulChip::<F>::new(config);
 
-    // Load {private, constant} values into the circuit
-    let a = field_chip.load_private(layouter.namespace(|| "load a"), self.a)?;
-    let b = field_chip.load_private(layouter.namespace(|| "load b"), self.b)?;
-    // Finally, tell the circuit how to use our Chip
-    let c = field_chip.mul(layouter.namespace(|| "a * b"), a, b)?;
+    let c = if self.inline {
+      // one region, and no copy constraints
+      field_chip.mul_inline(layouter.namespace(|| "a * b"), self.a, self.b)?
+    } else {
+      // Load {private, constant} values into the circuit
+      let a = field_chip.load_private(layouter.namespace(|| "load a"), self.a)?;
+      let b = field_chip.load_private(layouter.namespace(|| "load b"), self.b)?;
+      // Finally, tell the circuit how to use our Chip; three regions, and two copy constraints
+      field_chip.mul(layouter.namespace(|| "a * b"), a, b)?
+    };
 
     // and "return" the result as a public input to the circuit
     field_chip.expose_public(layouter.namespace(|| "expose result"), c, 0)
@@ -73,86 +82,73 @@ impl<F: Field> Circuit<F> for ScalarMulCircuit<F> {
 
 pub struct Workbench {
   name:            String,
-  params:          Params<VestaAffine>,
-  pk:              ProvingKey<VestaAffine>,
-  vk:              VerifyingKey<VestaAffine>,
-  circuit:         ScalarMulCircuit<Fp>,
-  expected_output: Fp,
-  rng:             rand::rngs::OsRng,
+  k:               u32,
+  params:          Params<Curve>,
+  pk:              ProvingKey<Curve>,
+  vk:              VerifyingKey<Curve>,
+  circuit:         ScalarMulCircuit<Scalar>,
+  expected_output: Scalar,
 }
 
-pub fn workbench() -> Workbench {
-  // ANCHOR: test-circuit
-  // 2^k is the number of rows in our circuit
-  let k = 4;
+/// `name` prefixes the criterion ids, so bench files don't overwrite each other's baselines; 2^k
+/// is the number of rows in our circuit; `inline` picks `mul_inline` over `mul`
+pub fn workbench(name: &str, k: u32, inline: bool) -> Workbench {
   // Instantiate the circuit with the private inputs.
   let (circuit, expected_output) = {
-    let a = Fp::from(2);
-    let b = Fp::from(3);
+    let a = Scalar::from(2);
+    let b = Scalar::from(3);
     let c = a * b;
     let (a, b) = (Value::known(a), Value::known(b));
-    (ScalarMulCircuit { a, b }, c)
+    (ScalarMulCircuit { a, b, inline }, c)
   };
 
   // Initialize the proving key
-  let params = Params::new(k);
-  let vk = keygen_vk(&params, &circuit).expect("keygen_vk should not fail");
-  let pk = keygen_pk(&params, vk.clone(), &circuit).expect("keygen_pk should not fail");
-
-  Workbench {
-    name: String::from("scalar_mul"),
-    params,
-    pk,
-    vk,
-    circuit,
-    expected_output,
-    rng: rand::rngs::OsRng,
-  }
+  let (params, pk, vk) = setup(k, &circuit);
+
+  Workbench { name: name.to_string(), k, params, pk, vk, circuit, expected_output }
 }
 
-pub fn bench_scalar_mul(w: Workbench, crit: &mut Criterion) {
-  let Workbench { params, pk, vk, circuit, expected_output, mut rng, name } = w;
-  let prover_str = format!("{}-prover", name);
+/// bench every workbench in one group per prover/verifier, so criterion can plot the scaling in k
+pub fn bench_scalar_mul(workbenches: Vec<Workbench>, crit: &mut Criterion) {
+  let name = workbenches[0].name.clone();
+  let prover_str = name.clone();
   let verifier_str = format!("{}-verifier", name);
 
-  crit.bench_function(&prover_str, |b| {
-    b.iter(|| {
-      // ref: https://github.com/zcash/halo2/blob/76b3f892a9d598923bbb5a747701fff44ae4c0ea/halo2_gadgets/benches/poseidon.rs#L178
-      // choose a hash function for FS challenges
-      // Why blake2b not poseidon?
-      // > We will replace BLAKE2b with an algebraic hash function in a later version. - Halo 2 authors
-      let mut transcript =
-        Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
-      create_proof(
-        &params,
-        &pk,
-        &[circuit.clone()],
-        &[&[&[expected_output]]],
-        &mut rng,
-        &mut transcript,
-      )
-      .unwrap();
-    })
-  });
-
-  // Create a proof
-  let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
-  create_proof(&params, &pk, &[circuit], &[&[&[expected_output]]], &mut rng, &mut transcript)
-    .expect("proof generation should not fail");
-  let proof = transcript.finalize();
-
-  crit.bench_function(&verifier_str, |b| {
-    b.iter(|| {
-      let strategy = SingleVerifier::new(&params);
-      let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
-      assert!(
-        verify_proof(&params, pk.get_vk(), strategy, &[&[&[expected_output]]], &mut transcript).is_ok()
-      );
+  let mut group = crit.benchmark_group(&prover_str);
+  // large k takes seconds per proof; keep the total run time reasonable
+  group.sample_size(10);
+  for w in &workbenches {
+    let Workbench { k, params, pk, circuit, expected_output, .. } = w;
+    group.bench_function(format!("k={k}"), |b| {
+      b.iter(|| prove(params, pk, circuit.clone(), &[&[*expected_output]]))
+    });
+  }
+  group.finish();
+
+  let mut group = crit.benchmark_group(&verifier_str);
+  for w in workbenches {
+    let Workbench { k, params, vk, pk, circuit, expected_output, .. } = w;
+
+    let proof = prove(&params, &pk, circuit, &[&[expected_output]]);
+
+    group.bench_function(format!("k={k}"), |b| {
+      b.iter(|| {
+        assert!(verify(&params, &vk, &[&[expected_output]], &proof).is_ok());
+      });
     });
-  });
+  }
+  group.finish();
 }
 
-fn run_bench(c: &mut Criterion) { bench_scalar_mul(workbench(), c); }
+fn run_bench(c: &mut Criterion) {
+  let workbenches = [4, 8, 12, 16].into_iter().map(|k| workbench("scalar_mul", k, false)).collect();
+  bench_scalar_mul(workbenches, c);
+
+  // compare against `mul_inline`, which skips the load regions and their copy constraints
+  let workbenches =
+    [4, 8, 12, 16].into_iter().map(|k| workbench("scalar_mul_inline", k, true)).collect();
+  bench_scalar_mul(workbenches, c);
+}
 
 criterion_group!(benches, run_bench);
 criterion_main!(benches);
@@ -0,0 +1,107 @@
+#![allow(unused_imports)]
+#![allow(unused_variables)]
+#![allow(dead_code)]
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use halo2_proofs::{
+  circuit::{SimpleFloorPlanner, Value},
+  pasta::{
+    group::ff::{Field, PrimeField},
+    pallas, vesta, Fp, Fq,
+  },
+  plonk::{keygen_pk, keygen_vk, Circuit, ConstraintSystem, ProvingKey, VerifyingKey},
+  poly::commitment::Params,
+};
+use halo_2_benches::{
+  gadgets::scalar_mul::*,
+  prover::{prove, setup, verify},
+  Curve, Scalar,
+};
+
+/// width of the scalar in bits
+const SCALAR_BITS: usize = 8;
+
+/// returning scalar * base, by double-and-add over the bits of scalar
+#[derive(Default, Clone)]
+pub struct ScalarMulBitsCircuit<F: Field> {
+  pub base:   Value<F>,
+  pub scalar: Value<F>,
+}
+
+impl<F: PrimeField> Circuit<F> for ScalarMulBitsCircuit<F> {
+  type Config = ScalarMulConfig;
+  type FloorPlanner = SimpleFloorPlanner;
+
+  fn without_witnesses(&self) -> Self { Self::default() }
+
+  fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+    // mul_add has fan-in 3, so need 3 advice cols
+    let advice = [meta.advice_column(), meta.advice_column(), meta.advice_column()];
+    let instance = meta.instance_column();
+    let constant = meta.fixed_column();
+    Self::Config::configure(meta, &advice, &[instance], constant)
+  }
+
+  fn synthesize(
+    &self,
+    config: Self::Config,
+    mut layouter: impl halo2_proofs::circuit::Layouter<F>,
+  ) -> Result<(), halo2_proofs::plonk::Error> {
+    let field_chip = ScalarMulChip::<F>::new(config);
+
+    let base = field_chip.load_private(layouter.namespace(|| "load base"), self.base)?;
+    let scalar = field_chip.load_private(layouter.namespace(|| "load scalar"), self.scalar)?;
+    // the decomposition also constrains each bit to be boolean
+    let bits = field_chip.to_bits(layouter.namespace(|| "scalar bits"), scalar, SCALAR_BITS)?;
+    let c = field_chip.scalar_mul_bits(layouter.namespace(|| "scalar * base"), base, &bits)?;
+
+    field_chip.expose_public(layouter.namespace(|| "expose result"), c, 0)
+  }
+}
+
+pub struct Workbench {
+  name:            String,
+  params:          Params<Curve>,
+  pk:              ProvingKey<Curve>,
+  vk:              VerifyingKey<Curve>,
+  circuit:         ScalarMulBitsCircuit<Scalar>,
+  expected_output: Scalar,
+}
+
+pub fn workbench() -> Workbench {
+  // 2^k is the number of rows in our circuit; each scalar bit costs a few regions
+  let k = 7;
+  let (circuit, expected_output) = {
+    let base = Scalar::from(7);
+    let scalar = Scalar::from(0b1011_0101);
+    let c = base * scalar;
+    (ScalarMulBitsCircuit { base: Value::known(base), scalar: Value::known(scalar) }, c)
+  };
+
+  let (params, pk, vk) = setup(k, &circuit);
+
+  Workbench { name: String::from("scalar_mul_bits"), params, pk, vk, circuit, expected_output }
+}
+
+pub fn bench_scalar_mul_bits(w: Workbench, crit: &mut Criterion) {
+  let Workbench { params, pk, vk, circuit, expected_output, name } = w;
+  let prover_str = format!("{}-prover", name);
+  let verifier_str = format!("{}-verifier", name);
+
+  crit.bench_function(&prover_str, |b| {
+    b.iter(|| prove(&params, &pk, circuit.clone(), &[&[expected_output]]))
+  });
+
+  let proof = prove(&params, &pk, circuit, &[&[expected_output]]);
+
+  crit.bench_function(&verifier_str, |b| {
+    b.iter(|| {
+      assert!(verify(&params, &vk, &[&[expected_output]], &proof).is_ok());
+    });
+  });
+}
+
+fn run_bench(c: &mut Criterion) { bench_scalar_mul_bits(workbench(), c); }
+
+criterion_group!(benches, run_bench);
+criterion_main!(benches);
@@ -0,0 +1,21 @@
+//! Prove a^2 * b^2 for a = 2, b = 3 end to end, printing the proof
+use halo2_proofs::pasta::{vesta, Fp};
+use halo_2_benches::{
+  circuits::nn_mul::NNMulCircuit,
+  prover::{prove, setup, verify},
+};
+
+fn main() {
+  // 2^k is the number of rows in our circuit
+  let k = 4;
+  let (circuit, expected_output) = NNMulCircuit::new(Fp::from(2), Fp::from(3));
+  let (params, pk, vk) = setup::<vesta::Affine, _>(k, &circuit);
+
+  let proof = prove(&params, &pk, circuit, &[&[expected_output]]);
+  let hex: String = proof.iter().map(|byte| format!("{byte:02x}")).collect();
+  println!("proof: {hex}");
+  println!("length: {} bytes", proof.len());
+
+  verify(&params, &vk, &[&[expected_output]], &proof).expect("proof should verify");
+  println!("OK");
+}
@@ -0,0 +1,171 @@
+#![allow(unused_imports)]
+#![allow(unused_variables)]
+#![allow(dead_code)]
+
+use std::marker::PhantomData;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use halo2_gadgets::poseidon::{
+  primitives::{self as poseidon, generate_constants, ConstantLength, Mds, Spec},
+  Hash, Pow5Chip, Pow5Config,
+};
+use halo2_proofs::{
+  circuit::{Layouter, SimpleFloorPlanner, Value},
+  pasta::{group::ff::Field, pallas, vesta, Fp, Fq},
+  plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance, ProvingKey, VerifyingKey},
+  poly::commitment::Params,
+};
+use halo_2_benches::{
+  prover::{prove, setup, verify},
+  Curve, Scalar,
+};
+
+// ref: https://github.com/zcash/halo2/blob/76b3f892a9d598923bbb5a747701fff44ae4c0ea/halo2_gadgets/benches/poseidon.rs
+/// returning the poseidon hash of an `L` element message
+#[derive(Clone, Copy)]
+pub struct HashCircuit<S, const WIDTH: usize, const RATE: usize, const L: usize>
+where S: Spec<Scalar, WIDTH, RATE> + Clone + Copy {
+  message: Value<[Scalar; L]>,
+  _spec:   PhantomData<S>,
+}
+
+#[derive(Debug, Clone)]
+pub struct HashConfig<const WIDTH: usize, const RATE: usize, const L: usize> {
+  input:           [Column<Advice>; L],
+  expected:        Column<Instance>,
+  poseidon_config: Pow5Config<Scalar, WIDTH, RATE>,
+}
+
+impl<S, const WIDTH: usize, const RATE: usize, const L: usize> Circuit<Scalar>
+  for HashCircuit<S, WIDTH, RATE, L>
+where S: Spec<Scalar, WIDTH, RATE> + Copy + Clone
+{
+  type Config = HashConfig<WIDTH, RATE, L>;
+  type FloorPlanner = SimpleFloorPlanner;
+
+  fn without_witnesses(&self) -> Self { Self { message: Value::unknown(), _spec: PhantomData } }
+
+  fn configure(meta: &mut ConstraintSystem<Scalar>) -> Self::Config {
+    let state = (0..WIDTH).map(|_| meta.advice_column()).collect::<Vec<_>>();
+    let expected = meta.instance_column();
+    meta.enable_equality(expected);
+    let partial_sbox = meta.advice_column();
+
+    // round constants
+    let rc_a = (0..WIDTH).map(|_| meta.fixed_column()).collect::<Vec<_>>();
+    let rc_b = (0..WIDTH).map(|_| meta.fixed_column()).collect::<Vec<_>>();
+
+    meta.enable_constant(rc_b[0]);
+
+    HashConfig {
+      // the message is absorbed in one go, so it is loaded into the rate portion of the state
+      input: state[..RATE].try_into().unwrap(),
+      expected,
+      poseidon_config: Pow5Chip::configure::<S>(
+        meta,
+        state.try_into().unwrap(),
+        partial_sbox,
+        rc_a.try_into().unwrap(),
+        rc_b.try_into().unwrap(),
+      ),
+    }
+  }
+
+  fn synthesize(
+    &self,
+    config: Self::Config,
+    mut layouter: impl Layouter<Scalar>,
+  ) -> Result<(), Error> {
+    let chip = Pow5Chip::construct(config.poseidon_config.clone());
+
+    let message = layouter.assign_region(
+      || "load message",
+      |mut region| {
+        let message_word = |i: usize| {
+          let value = self.message.map(|message_vals| message_vals[i]);
+          region.assign_advice(|| format!("load message_{i}"), config.input[i], 0, || value)
+        };
+
+        let message: Result<Vec<_>, Error> = (0..L).map(message_word).collect();
+        Ok(message?.try_into().unwrap())
+      },
+    )?;
+
+    let hasher =
+      Hash::<_, _, S, ConstantLength<L>, WIDTH, RATE>::init(chip, layouter.namespace(|| "init"))?;
+    let output = hasher.hash(layouter.namespace(|| "hash"), message)?;
+
+    // and "return" the digest as a public input to the circuit
+    layouter.constrain_instance(output.cell(), config.expected, 0)
+  }
+}
+
+/// the standard Pow5 spec: 8 full rounds, 56 partial rounds, x^5 s-box
+#[derive(Debug, Clone, Copy)]
+pub struct MySpec<const WIDTH: usize, const RATE: usize>;
+
+impl<const WIDTH: usize, const RATE: usize> Spec<Scalar, WIDTH, RATE> for MySpec<WIDTH, RATE> {
+  fn full_rounds() -> usize { 8 }
+
+  fn partial_rounds() -> usize { 56 }
+
+  fn sbox(val: Scalar) -> Scalar { val.pow_vartime([5]) }
+
+  fn secure_mds() -> usize { 0 }
+
+  fn constants() -> (Vec<[Scalar; WIDTH]>, Mds<Scalar, WIDTH>, Mds<Scalar, WIDTH>) {
+    generate_constants::<_, Self, WIDTH, RATE>()
+  }
+}
+
+const WIDTH: usize = 3;
+const RATE: usize = 2;
+const L: usize = 2;
+
+type PoseidonCircuit = HashCircuit<MySpec<WIDTH, RATE>, WIDTH, RATE, L>;
+
+pub struct Workbench {
+  name:            String,
+  params:          Params<Curve>,
+  pk:              ProvingKey<Curve>,
+  vk:              VerifyingKey<Curve>,
+  circuit:         PoseidonCircuit,
+  expected_output: Scalar,
+}
+
+pub fn workbench() -> Workbench {
+  // 2^k is the number of rows in our circuit; the 64 poseidon rounds don't fit in fewer
+  let k = 7;
+  // Instantiate the circuit with the private inputs.
+  let message = [Scalar::from(2), Scalar::from(3)];
+  let expected_output =
+    poseidon::Hash::<_, MySpec<WIDTH, RATE>, ConstantLength<L>, WIDTH, RATE>::init().hash(message);
+  let circuit = HashCircuit { message: Value::known(message), _spec: PhantomData };
+
+  let (params, pk, vk) = setup(k, &circuit);
+
+  Workbench { name: String::from("poseidon"), params, pk, vk, circuit, expected_output }
+}
+
+pub fn bench_poseidon(w: Workbench, crit: &mut Criterion) {
+  let Workbench { params, pk, vk, circuit, expected_output, name } = w;
+  let prover_str = format!("{}-prover", name);
+  let verifier_str = format!("{}-verifier", name);
+
+  crit.bench_function(&prover_str, |b| {
+    b.iter(|| prove(&params, &pk, circuit, &[&[expected_output]]))
+  });
+
+  let proof = prove(&params, &pk, circuit, &[&[expected_output]]);
+
+  crit.bench_function(&verifier_str, |b| {
+    b.iter(|| {
+      assert!(verify(&params, &vk, &[&[expected_output]], &proof).is_ok());
+    });
+  });
+}
+
+fn run_bench(c: &mut Criterion) { bench_poseidon(workbench(), c); }
+
+criterion_group!(benches, run_bench);
+criterion_main!(benches);
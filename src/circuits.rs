@@ -0,0 +1,8 @@
+pub mod accumulate;
+pub mod affine;
+pub mod iterated_mul;
+pub mod lookup_table;
+pub mod matmul;
+pub mod mul_layout;
+pub mod nn_mul;
+pub mod poly;
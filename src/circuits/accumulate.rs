@@ -0,0 +1,78 @@
+//! A circuit keeping a running total over private addends, exposing the final total
+use halo2_proofs::{
+  circuit::{Layouter, SimpleFloorPlanner, Value},
+  pasta::group::ff::{Field, PrimeField},
+  plonk::{Circuit, ConstraintSystem, Error},
+};
+
+use crate::{
+  gadgets::scalar_mul::{ScalarMulChip, ScalarMulConfig, ScalarMulInstructions},
+  reference,
+};
+
+/// returning sum(addends), accumulated one addend at a time from zero, as an online aggregation
+/// would
+#[derive(Default, Clone)]
+pub struct AccumulateCircuit<F: Field> {
+  pub addends: Vec<Value<F>>,
+}
+
+impl<F: Field> AccumulateCircuit<F> {
+  /// Build the circuit for private `addends`, along with its expected public output
+  pub fn new(addends: &[F]) -> (Self, F) {
+    let total = addends.iter().fold(F::ZERO, |total, addend| reference::add(total, *addend));
+    (Self { addends: addends.iter().copied().map(Value::known).collect() }, total)
+  }
+
+  /// the number of add gates, one per addend
+  pub fn gate_count(&self) -> u64 { self.addends.len() as u64 }
+}
+
+impl<F: PrimeField> Circuit<F> for AccumulateCircuit<F> {
+  type Config = ScalarMulConfig;
+  type FloorPlanner = SimpleFloorPlanner;
+
+  // the number of addends fixes the layout, so keep it when dropping the witnesses
+  fn without_witnesses(&self) -> Self {
+    Self { addends: vec![Value::unknown(); self.addends.len()] }
+  }
+
+  fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+    let advice = [meta.advice_column(), meta.advice_column(), meta.advice_column()];
+    let instance = meta.instance_column();
+    let constant = meta.fixed_column();
+    Self::Config::configure(meta, &advice, &[instance], constant)
+  }
+
+  fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+    let field_chip = ScalarMulChip::<F>::new(config);
+
+    let addends =
+      field_chip.load_private_vec(layouter.namespace(|| "load addends"), &self.addends)?;
+    let mut total = field_chip.zero(layouter.namespace(|| "total = 0"))?;
+    for (i, addend) in addends.into_iter().enumerate() {
+      total =
+        field_chip.accumulate(layouter.namespace(|| format!("total + {i}")), total, addend)?;
+    }
+
+    field_chip.expose_public(layouter.namespace(|| "expose total"), total, 0)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use halo2_proofs::pasta::Fp;
+
+  use super::*;
+  use crate::testing::{assert_fails, assert_satisfied};
+
+  const K: u32 = 5;
+
+  #[test]
+  fn totals_its_addends() {
+    let (circuit, total) = AccumulateCircuit::new(&[1, 2, 3, 4].map(Fp::from));
+    assert_eq!(total, Fp::from(10));
+    assert_satisfied(K, &circuit, vec![vec![total]]);
+    assert_fails(K, &circuit, vec![vec![total + Fp::ONE]]);
+  }
+}
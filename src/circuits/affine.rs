@@ -0,0 +1,86 @@
+//! A circuit computing one neuron, the weighted sum of private inputs plus a bias
+use halo2_proofs::{
+  circuit::{Layouter, SimpleFloorPlanner, Value},
+  pasta::group::ff::{Field, PrimeField},
+  plonk::{Circuit, ConstraintSystem, Error},
+};
+
+use crate::{
+  gadgets::scalar_mul::{ScalarMulChip, ScalarMulConfig, ScalarMulInstructions},
+  reference,
+};
+
+/// returning dot(inputs, weights) + bias
+#[derive(Default, Clone)]
+pub struct AffineCircuit<F: Field> {
+  pub inputs:  Vec<Value<F>>,
+  pub weights: Vec<Value<F>>,
+  /// a constant of the circuit, fixed at keygen, unlike the private inputs and weights
+  pub bias:    F,
+}
+
+impl<F: Field> AffineCircuit<F> {
+  /// Build the circuit for private `inputs` and `weights` of equal length, along with its expected
+  /// public output
+  pub fn new(inputs: &[F], weights: &[F], bias: F) -> (Self, F) {
+    let c = reference::add(reference::dot(inputs, weights), bias);
+    let (inputs, weights) =
+      (inputs.iter().copied().map(Value::known), weights.iter().copied().map(Value::known));
+    (Self { inputs: inputs.collect(), weights: weights.collect(), bias }, c)
+  }
+
+  /// the number of multiplication gates, one per input
+  pub fn gate_count(&self) -> u64 { self.inputs.len() as u64 }
+}
+
+impl<F: PrimeField> Circuit<F> for AffineCircuit<F> {
+  type Config = ScalarMulConfig;
+  type FloorPlanner = SimpleFloorPlanner;
+
+  // the dimension and the bias fix the layout, so keep them when dropping the witnesses
+  fn without_witnesses(&self) -> Self {
+    Self {
+      inputs:  vec![Value::unknown(); self.inputs.len()],
+      weights: vec![Value::unknown(); self.weights.len()],
+      bias:    self.bias,
+    }
+  }
+
+  fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+    let advice = [meta.advice_column(), meta.advice_column(), meta.advice_column()];
+    let instance = meta.instance_column();
+    let constant = meta.fixed_column();
+    Self::Config::configure(meta, &advice, &[instance], constant)
+  }
+
+  fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+    let field_chip = ScalarMulChip::<F>::new(config);
+
+    let inputs = field_chip.load_private_vec(layouter.namespace(|| "load inputs"), &self.inputs)?;
+    let weights =
+      field_chip.load_private_vec(layouter.namespace(|| "load weights"), &self.weights)?;
+    let sum = field_chip.dot(layouter.namespace(|| "inputs . weights"), &inputs, &weights)?;
+    let c = field_chip.add_constant(layouter.namespace(|| "+ bias"), sum, self.bias)?;
+
+    field_chip.expose_public(layouter.namespace(|| "expose result"), c, 0)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use halo2_proofs::pasta::{group::ff::Field, Fp};
+
+  use super::*;
+  use crate::testing::{assert_fails, assert_satisfied};
+
+  const K: u32 = 5;
+
+  #[test]
+  fn exposes_dot_plus_bias() {
+    let (inputs, weights) = ([1, 2, 3].map(Fp::from), [4, 5, 6].map(Fp::from));
+    let (circuit, c) = AffineCircuit::new(&inputs, &weights, Fp::from(7));
+    assert_eq!(c, Fp::from(39));
+    assert_satisfied(K, &circuit, vec![vec![c]]);
+    assert_fails(K, &circuit, vec![vec![c + Fp::ONE]]);
+  }
+}
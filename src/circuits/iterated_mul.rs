@@ -0,0 +1,63 @@
+//! A circuit enabling `s_mul` on `iters` consecutive rows of one region, exposing x^(iters + 1)
+use halo2_proofs::{
+  circuit::{Layouter, SimpleFloorPlanner, Value},
+  pasta::group::ff::{Field, PrimeField},
+  plonk::{Circuit, ConstraintSystem, Error},
+};
+
+use crate::{
+  circuits::mul_layout::ConfigureWithLayout,
+  gadgets::scalar_mul::{MulLayout, ScalarMulChip, ScalarMulConfig, ScalarMulInstructions},
+  reference,
+};
+
+/// returning x^(iters + 1), with every mul in one region, where `NNMulCircuit` uses one region per
+/// mul
+#[derive(Default, Clone)]
+pub struct IteratedMulCircuit<F: Field> {
+  pub x:     Value<F>,
+  pub iters: usize,
+}
+
+impl<F: Field> IteratedMulCircuit<F> {
+  /// Build the circuit for private input `x`, along with its expected public output
+  pub fn new(x: F, iters: usize) -> (Self, F) {
+    let c = reference::pow(x, iters as u64 + 1);
+    (Self { x: Value::known(x), iters }, c)
+  }
+
+  /// the number of multiplication gates, one per iteration
+  pub fn gate_count(&self) -> u64 { self.iters as u64 }
+}
+
+impl<F: PrimeField> Circuit<F> for IteratedMulCircuit<F> {
+  type Config = ScalarMulConfig;
+  type FloorPlanner = SimpleFloorPlanner;
+
+  // the iteration count fixes the layout, so keep it when dropping the witness
+  fn without_witnesses(&self) -> Self { Self { iters: self.iters, ..Self::default() } }
+
+  fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+    Self::configure_with_layout(meta, MulLayout::default())
+  }
+
+  fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+    let field_chip = ScalarMulChip::<F>::new(config);
+
+    let x = field_chip.load_private(layouter.namespace(|| "load x"), self.x)?;
+    // `mul_chained` enables s_mul on each row of a single region
+    let factors = vec![x.clone(); self.iters];
+    let c = field_chip.mul_chained(layouter.namespace(|| "iterated mul"), x, &factors)?;
+
+    field_chip.expose_public(layouter.namespace(|| "expose result"), c, 0)
+  }
+}
+
+impl<F: PrimeField> ConfigureWithLayout<F> for IteratedMulCircuit<F> {
+  fn configure_with_layout(meta: &mut ConstraintSystem<F>, mul_layout: MulLayout) -> Self::Config {
+    let advice = [meta.advice_column(), meta.advice_column(), meta.advice_column()];
+    let instance = meta.instance_column();
+    let constant = meta.fixed_column();
+    Self::Config::configure_with_layout(meta, &advice, &[instance], constant, mul_layout)
+  }
+}
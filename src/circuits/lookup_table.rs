@@ -0,0 +1,117 @@
+//! A circuit squaring a private input by looking it up in a fixed table of squares, exposing x^2
+use halo2_proofs::{
+  circuit::{Layouter, SimpleFloorPlanner, Value},
+  pasta::group::ff::{Field, PrimeField},
+  plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Instance, Selector, TableColumn},
+  poly::Rotation,
+};
+
+use crate::reference;
+
+/// The table holds (x, x^2) for x in `0..TABLE_SIZE`; squaring anything else fails the lookup
+pub const TABLE_SIZE: u64 = 16;
+
+/// returning x^2, for x in the table
+#[derive(Default, Clone)]
+pub struct LookupTableCircuit<F: Field> {
+  pub x: Value<F>,
+}
+
+impl<F: Field> LookupTableCircuit<F> {
+  /// Build the circuit for private input `x`, along with its expected public output
+  pub fn new(x: F) -> (Self, F) { (Self { x: Value::known(x) }, reference::pow(x, 2)) }
+}
+
+#[derive(Clone, Debug)]
+pub struct LookupTableConfig {
+  /// x and x^2
+  pub advice:   [Column<Advice>; 2],
+  pub instance: Column<Instance>,
+  /// the fixed table, x and x^2
+  pub table:    [TableColumn; 2],
+  /// enables the lookup; a lookup can't take a simple selector
+  pub s_lookup: Selector,
+}
+
+impl<F: PrimeField> Circuit<F> for LookupTableCircuit<F> {
+  type Config = LookupTableConfig;
+  type FloorPlanner = SimpleFloorPlanner;
+
+  fn without_witnesses(&self) -> Self { Self::default() }
+
+  fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+    let advice = [meta.advice_column(), meta.advice_column()];
+    let instance = meta.instance_column();
+    let table = [meta.lookup_table_column(), meta.lookup_table_column()];
+    let s_lookup = meta.complex_selector();
+
+    // only the output is copied, into the instance column
+    meta.enable_equality(advice[1]);
+    meta.enable_equality(instance);
+
+    // | a0 | a1  | s_lookup |
+    // |----|-----|----------|
+    // | x  | x^2 | s_lookup |
+    //
+    // with s_lookup off the input is (0, 0), which is in the table
+    meta.lookup(|meta| {
+      let x = meta.query_advice(advice[0], Rotation::cur());
+      let xx = meta.query_advice(advice[1], Rotation::cur());
+      let s_lookup = meta.query_selector(s_lookup);
+
+      vec![(s_lookup.clone() * x, table[0]), (s_lookup * xx, table[1])]
+    });
+
+    LookupTableConfig { advice, instance, table, s_lookup }
+  }
+
+  fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+    layouter.assign_table(
+      || "squares",
+      |mut table| {
+        for x in 0..TABLE_SIZE {
+          let row = x as usize;
+          let (x, xx) = (Value::known(F::from(x)), Value::known(F::from(x * x)));
+          table.assign_cell(|| format!("x {row}"), config.table[0], row, || x)?;
+          table.assign_cell(|| format!("x^2 {row}"), config.table[1], row, || xx)?;
+        }
+        Ok(())
+      },
+    )?;
+
+    let xx = layouter.assign_region(
+      || "square by lookup",
+      |mut region| {
+        config.s_lookup.enable(&mut region, 0)?;
+        region.assign_advice(|| "x", config.advice[0], 0, || self.x)?;
+        region.assign_advice(|| "x^2", config.advice[1], 0, || self.x.map(|x| x.square()))
+      },
+    )?;
+
+    layouter.constrain_instance(xx.cell(), config.instance, 0)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use halo2_proofs::pasta::Fp;
+
+  use super::*;
+  use crate::testing::{assert_fails, assert_satisfied};
+
+  // the 16-row table needs more than the 2^4 rows' usable ones
+  const K: u32 = 5;
+
+  #[test]
+  fn squares_in_the_table() {
+    let (circuit, xx) = LookupTableCircuit::new(Fp::from(3));
+    assert_satisfied(K, &circuit, vec![vec![xx]]);
+  }
+
+  // one past the end fails the lookup, even given its true square
+  #[test]
+  fn rejects_past_the_table() {
+    let (circuit, xx) = LookupTableCircuit::new(Fp::from(TABLE_SIZE));
+    assert_fails(K, &circuit, vec![vec![xx]]);
+  }
+}
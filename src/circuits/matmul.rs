@@ -0,0 +1,110 @@
+//! A circuit multiplying two private matrices, exposing the product, as a dense NN layer would
+use halo2_proofs::{
+  circuit::{Layouter, SimpleFloorPlanner, Value},
+  pasta::group::ff::{Field, PrimeField},
+  plonk::{Circuit, ConstraintSystem, Error},
+};
+
+use crate::{
+  gadgets::scalar_mul::{InstanceWriter, ScalarMulChip, ScalarMulConfig, ScalarMulInstructions},
+  reference,
+};
+
+/// returning a * b for an m x n matrix a and an n x p matrix b, all row-major
+#[derive(Default, Clone)]
+pub struct MatMulCircuit<F: Field> {
+  pub a:    Vec<Value<F>>,
+  pub b:    Vec<Value<F>>,
+  /// (m, n, p)
+  pub dims: (usize, usize, usize),
+}
+
+impl<F: Field> MatMulCircuit<F> {
+  /// Build the circuit for private row-major matrices `a` and `b`, along with the expected
+  /// row-major product, exposed in that order in the first instance column
+  pub fn new(a: &[F], b: &[F], dims: (usize, usize, usize)) -> (Self, Vec<F>) {
+    let (m, n, p) = dims;
+    assert_eq!(a.len(), m * n, "a should be m x n");
+    assert_eq!(b.len(), n * p, "b should be n x p");
+
+    let c = (0..m)
+      .flat_map(|i| {
+        (0..p).map(move |j| {
+          let col: Vec<F> = (0..n).map(|k| b[k * p + j]).collect();
+          reference::dot(&a[i * n..(i + 1) * n], &col)
+        })
+      })
+      .collect();
+    let (a, b) = (a.iter().copied().map(Value::known), b.iter().copied().map(Value::known));
+    (Self { a: a.collect(), b: b.collect(), dims }, c)
+  }
+
+  /// the number of multiplication gates: n per output cell, of which there are m * p
+  pub fn gate_count(&self) -> u64 {
+    let (m, n, p) = self.dims;
+    (m * n * p) as u64
+  }
+}
+
+impl<F: PrimeField> Circuit<F> for MatMulCircuit<F> {
+  type Config = ScalarMulConfig;
+  type FloorPlanner = SimpleFloorPlanner;
+
+  // the dimensions fix the layout, so keep them when dropping the witnesses
+  fn without_witnesses(&self) -> Self {
+    Self {
+      a:    vec![Value::unknown(); self.a.len()],
+      b:    vec![Value::unknown(); self.b.len()],
+      dims: self.dims,
+    }
+  }
+
+  fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+    let advice = [meta.advice_column(), meta.advice_column(), meta.advice_column()];
+    let instance = meta.instance_column();
+    let constant = meta.fixed_column();
+    Self::Config::configure(meta, &advice, &[instance], constant)
+  }
+
+  fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+    // the product is exposed row-major, which is also the order it's computed in
+    let mut output = InstanceWriter::new(config.instance[0]);
+    let field_chip = ScalarMulChip::<F>::new(config);
+    let (m, n, p) = self.dims;
+
+    let a = field_chip.load_private_vec(layouter.namespace(|| "load a"), &self.a)?;
+    let b = field_chip.load_private_vec(layouter.namespace(|| "load b"), &self.b)?;
+
+    for i in 0..m {
+      let row = &a[i * n..(i + 1) * n];
+      for j in 0..p {
+        let col: Vec<_> = (0..n).map(|k| b[k * p + j].clone()).collect();
+        let c = field_chip.dot(layouter.namespace(|| format!("c_{i}{j}")), row, &col)?;
+        output.expose_next(layouter.namespace(|| format!("expose c_{i}{j}")), c)?;
+      }
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use halo2_proofs::pasta::{group::ff::Field, Fp};
+
+  use super::*;
+  use crate::testing::{assert_fails, assert_satisfied};
+
+  const K: u32 = 6;
+
+  #[test]
+  fn multiplies_2x2() {
+    let (a, b) = ([1, 2, 3, 4].map(Fp::from), [5, 6, 7, 8].map(Fp::from));
+    let (circuit, c) = MatMulCircuit::new(&a, &b, (2, 2, 2));
+    assert_eq!(c, [19, 22, 43, 50].map(Fp::from));
+    assert_satisfied(K, &circuit, vec![c.clone()]);
+
+    let mut wrong = c;
+    wrong[3] += Fp::ONE;
+    assert_fails(K, &circuit, vec![wrong]);
+  }
+}
@@ -0,0 +1,142 @@
+//! A wrapper configuring any circuit over the shared chip with a chosen `MulLayout`
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+  circuit::Layouter,
+  pasta::group::ff::{Field, PrimeField},
+  plonk::{Circuit, ConstraintSystem, Error},
+};
+
+use crate::gadgets::scalar_mul::{MulLayout, ScalarMulConfig};
+
+/// A `MulLayout` named by a type; `configure` takes no arguments, so `WithMulLayout` has to get
+/// its layout from one
+pub trait Layout {
+  const LAYOUT: MulLayout;
+}
+
+/// Marks `MulLayout::TwoColNextRow`
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TwoColNextRow;
+
+/// Marks `MulLayout::ThreeColSameRow`
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ThreeColSameRow;
+
+/// Marks `MulLayout::Packed`
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Packed;
+
+impl Layout for TwoColNextRow {
+  const LAYOUT: MulLayout = MulLayout::TwoColNextRow;
+}
+
+impl Layout for ThreeColSameRow {
+  const LAYOUT: MulLayout = MulLayout::ThreeColSameRow;
+}
+
+impl Layout for Packed {
+  const LAYOUT: MulLayout = MulLayout::Packed;
+}
+
+/// A circuit over the shared chip that can build its config with any `MulLayout`, through
+/// `ScalarMulConfig::configure_with_layout`; its own `configure` is the same with the default
+pub trait ConfigureWithLayout<F: Field>: Circuit<F, Config = ScalarMulConfig> {
+  fn configure_with_layout(meta: &mut ConstraintSystem<F>, mul_layout: MulLayout) -> Self::Config;
+}
+
+/// `C`, configured over its own columns as `C` configures itself, but with its `mul` gate laid out
+/// per `L`, e.g. `WithMulLayout::<_, Packed>::new(circuit)`
+#[derive(Default, Clone)]
+pub struct WithMulLayout<C, L> {
+  pub circuit: C,
+  _layout:     PhantomData<L>,
+}
+
+impl<C, L: Layout> WithMulLayout<C, L> {
+  pub fn new(circuit: C) -> Self { Self { circuit, _layout: PhantomData } }
+}
+
+impl<F, C, L> Circuit<F> for WithMulLayout<C, L>
+where
+  F: PrimeField,
+  C: ConfigureWithLayout<F>,
+  L: Layout,
+{
+  type Config = ScalarMulConfig;
+  type FloorPlanner = C::FloorPlanner;
+
+  fn without_witnesses(&self) -> Self { Self::new(self.circuit.without_witnesses()) }
+
+  fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+    C::configure_with_layout(meta, L::LAYOUT)
+  }
+
+  fn synthesize(&self, config: Self::Config, layouter: impl Layouter<F>) -> Result<(), Error> {
+    self.circuit.synthesize(config, layouter)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use halo2_proofs::pasta::{group::ff::Field, Fp};
+
+  use super::*;
+  use crate::{
+    circuits::{iterated_mul::IteratedMulCircuit, nn_mul::NNMulCircuit},
+    cost::describe_cost,
+    testing::{assert_fails, assert_satisfied},
+  };
+
+  // the chain needs more than the 2^4 rows' usable ones under `Packed`
+  const K: u32 = 5;
+
+  // `NNMulCircuit` and a chain of two muls accept their true output and reject a wrong one
+  fn assert_layout_correct<L: Layout>() {
+    let (circuit, c) = NNMulCircuit::new(Fp::from(2), Fp::from(3));
+    let circuit = WithMulLayout::<_, L>::new(circuit);
+    assert_satisfied(K, &circuit, vec![vec![c]]);
+    assert_fails(K, &circuit, vec![vec![c + Fp::ONE]]);
+
+    let (chain, c) = IteratedMulCircuit::new(Fp::from(3), 2);
+    let chain = WithMulLayout::<_, L>::new(chain);
+    assert_satisfied(K, &chain, vec![vec![c]]);
+    assert_fails(K, &chain, vec![vec![c + Fp::ONE]]);
+  }
+
+  // the wrapped circuit's columns, and no more selectors or constraints than its default layout;
+  // only the gate count differs, a non-default mul taking a gate of its own outside `arith`
+  fn assert_configures_like_default<L: Layout>() {
+    let (circuit, _) = NNMulCircuit::new(Fp::from(2), Fp::from(3));
+    let default = describe_cost(K, &circuit);
+    let wrapped = describe_cost(K, &WithMulLayout::<_, L>::new(circuit));
+    assert_eq!(
+      (wrapped.advice, wrapped.fixed, wrapped.instance, wrapped.selectors),
+      (default.advice, default.fixed, default.instance, default.selectors)
+    );
+
+    let constraints = |meta: &ConstraintSystem<Fp>| {
+      meta.gates().iter().map(|gate| gate.polynomials().len()).sum::<usize>()
+    };
+    let mut default = ConstraintSystem::<Fp>::default();
+    NNMulCircuit::<Fp>::configure(&mut default);
+    let mut meta = ConstraintSystem::<Fp>::default();
+    let config = WithMulLayout::<NNMulCircuit<Fp>, L>::configure(&mut meta);
+    assert_eq!(config.mul_layout, L::LAYOUT);
+    assert_eq!(constraints(&meta), constraints(&default));
+  }
+
+  #[test]
+  fn configures_like_default() {
+    assert_configures_like_default::<TwoColNextRow>();
+    assert_configures_like_default::<ThreeColSameRow>();
+    assert_configures_like_default::<Packed>();
+  }
+
+  #[test]
+  fn every_layout_is_correct() {
+    assert_layout_correct::<TwoColNextRow>();
+    assert_layout_correct::<ThreeColSameRow>();
+    assert_layout_correct::<Packed>();
+  }
+}
@@ -0,0 +1,203 @@
+//! A circuit squaring and multiplying two private inputs, exposing a^2 * b^2
+use std::time::{Duration, Instant};
+
+use halo2_proofs::{
+  circuit::{Layouter, SimpleFloorPlanner, Value},
+  pasta::{
+    group::ff::{Field, PrimeField},
+    Fp,
+  },
+  plonk::{Circuit, ConstraintSystem, Error},
+};
+
+use crate::{
+  circuits::mul_layout::ConfigureWithLayout,
+  gadgets::scalar_mul::{MulLayout, ScalarMulChip, ScalarMulConfig, ScalarMulInstructions},
+  prover::{prove, setup},
+  reference, Curve, Scalar,
+};
+
+/// returning a^2 * b^2
+#[derive(Default, Clone)]
+pub struct NNMulCircuit<F: Field> {
+  pub a: Value<F>,
+  pub b: Value<F>,
+}
+
+impl<F: Field> NNMulCircuit<F> {
+  /// Build the circuit for private inputs `a` and `b`, along with its expected public output
+  pub fn new(a: F, b: F) -> (Self, F) {
+    let c = reference::mul(reference::pow(a, 2), reference::pow(b, 2));
+    (Self { a: Value::known(a), b: Value::known(b) }, c)
+  }
+
+  /// the number of multiplication gates: two squares and a mul
+  pub fn gate_count(&self) -> u64 { 3 }
+}
+
+impl<F: PrimeField> NNMulCircuit<F> {
+  /// `new` from integer inputs, for benches iterating over input magnitudes
+  pub fn from_u64s(a: u64, b: u64) -> (Self, F) { Self::new(F::from(a), F::from(b)) }
+}
+
+impl<F: PrimeField> Circuit<F> for NNMulCircuit<F> {
+  // the chip needs to be configured
+  // field choice for the Circuit, see below
+  // can have Circuit config overlap with Chip config since only one Chip
+  type Config = ScalarMulConfig;
+  // algorithm to plan table layout, using the default here
+  type FloorPlanner = SimpleFloorPlanner;
+
+  // typically just default
+  fn without_witnesses(&self) -> Self { Self::default() }
+
+  // describe exact gate/column arrangement
+  fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+    Self::configure_with_layout(meta, MulLayout::default())
+  }
+
+  // Create the circuit WRT the constraint system
+  fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+    // load any used arithmetic chips; see below for the construction of our chip
+    let field_chip = ScalarMulChip::<F>::new(config);
+
+    // Load {private, constant} values into the circuit
+    let a = field_chip.load_private(layouter.namespace(|| "load a"), self.a)?;
+    let b = field_chip.load_private(layouter.namespace(|| "load b"), self.b)?;
+    // Finally, tell the circuit how to use our Chip
+    let aa = field_chip.square(layouter.namespace(|| "a * a"), a)?;
+    let bb = field_chip.square(layouter.namespace(|| "b * b"), b)?;
+    let c = field_chip.mul(layouter.namespace(|| "aa * bb"), aa, bb)?;
+
+    // and "return" the result as a public input to the circuit
+    field_chip.expose_public(layouter.namespace(|| "expose result"), c, 0)
+  }
+}
+
+impl<F: PrimeField> ConfigureWithLayout<F> for NNMulCircuit<F> {
+  fn configure_with_layout(meta: &mut ConstraintSystem<F>, mul_layout: MulLayout) -> Self::Config {
+    // used for IO; the widest gate (mul_add) has fan-in 3, so need 3 advice cols
+    let advice = [meta.advice_column(), meta.advice_column(), meta.advice_column()];
+    // store public inputs in Instance columns
+    let instance = meta.instance_column();
+    // for loading a constant
+    let constant = meta.fixed_column();
+    // return the column configuration
+    Self::Config::configure_with_layout(meta, &advice, &[instance], constant, mul_layout)
+  }
+}
+
+/// The instances `circuit` exposes, one vec per instance column, computed natively from its private
+/// inputs, so `MockProver::run` and `prove` share one source of truth; panics on unknown witnesses
+pub fn public_inputs_of(circuit: &NNMulCircuit<Fp>) -> Vec<Vec<Fp>> {
+  // `Value` hides its contents, so read them out through `map`
+  let mut c = None;
+  circuit.a.zip(circuit.b).map(|(a, b)| {
+    c = Some(reference::mul(reference::pow(a, 2), reference::pow(b, 2)));
+  });
+  vec![vec![c.expect("public inputs need known witnesses")]]
+}
+
+/// Size in bytes of a proof of the circuit over 2^k rows
+pub fn proof_size(k: u32) -> usize {
+  let (circuit, c) = NNMulCircuit::new(Scalar::from(2), Scalar::from(3));
+  let (params, pk, _) = setup::<Curve, _>(k, &circuit);
+  prove(&params, &pk, circuit, &[&[c]]).len()
+}
+
+/// For each k: (k, multiplication gates, proof bytes, proving time), as rows for a report
+pub fn sweep(ks: &[u32]) -> Vec<(u32, usize, usize, Duration)> {
+  ks.iter()
+    .map(|&k| {
+      let (circuit, c) = NNMulCircuit::new(Scalar::from(2), Scalar::from(3));
+      let gates = circuit.gate_count() as usize;
+      let (params, pk, _) = setup::<Curve, _>(k, &circuit);
+
+      let start = Instant::now();
+      let proof = prove(&params, &pk, circuit, &[&[c]]);
+      (k, gates, proof.len(), start.elapsed())
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use halo2_proofs::{
+    dev::MockProver,
+    pasta::{group::ff::Field, vesta, Fp},
+  };
+  use rand::SeedableRng;
+  use rand_chacha::ChaCha20Rng;
+
+  use super::*;
+  use crate::{
+    prover::prove_and_verify,
+    testing::{assert_fails, assert_satisfied, SelfCheckingCircuit},
+  };
+
+  const K: u32 = 4;
+
+  // the link between the output cell and the instance column bites
+  #[test]
+  fn rejects_a_wrong_instance() {
+    let (circuit, c) = NNMulCircuit::new(Fp::from(2), Fp::from(3));
+    assert_satisfied(K, &circuit, vec![vec![c]]);
+    assert_fails(K, &circuit, vec![vec![c + Fp::ONE]]);
+  }
+
+  // both under the `MockProver` and through a real proof
+  #[test]
+  fn public_inputs_verify() {
+    let (circuit, _) = NNMulCircuit::new(Fp::from(2), Fp::from(3));
+    let public_inputs = public_inputs_of(&circuit);
+    assert_satisfied(K, &circuit, public_inputs.clone());
+
+    let public_inputs: Vec<&[Fp]> = public_inputs.iter().map(Vec::as_slice).collect();
+    assert!(
+      prove_and_verify::<vesta::Affine, _>(K, circuit, &public_inputs),
+      "proof should verify"
+    );
+  }
+
+  // integer inputs up to `u64::MAX`, whose square overflows a u64 but not the field
+  #[test]
+  fn from_u64s() {
+    for (a, b) in [(0, 3), (2, 3), (1, u64::MAX), (u64::MAX, u64::MAX)] {
+      let (circuit, c) = NNMulCircuit::from_u64s(a, b);
+      let (a, b) = (Fp::from(a), Fp::from(b));
+      assert_eq!(c, a.square() * b.square(), "reference mismatch for ({a:?}, {b:?})");
+      assert_satisfied(K, &circuit, vec![vec![c]]);
+    }
+  }
+
+  // random input pairs from a seeded rng, each checked against the native a^2 * b^2; a failing
+  // case reports its index, so the same seed reproduces it
+  #[test]
+  fn random_inputs_match_the_reference() {
+    let seed = 0x5eed;
+    let mut rng = ChaCha20Rng::seed_from_u64(seed);
+    for i in 0..300 {
+      let (a, b) = (Fp::random(&mut rng), Fp::random(&mut rng));
+      let checked = SelfCheckingCircuit::nn_mul(a, b);
+      let prover = MockProver::run(K, &checked.circuit, vec![checked.expected.clone()])
+        .expect("synthesis should not fail");
+      assert_eq!(prover.verify(), Ok(()), "case {i} of seed {seed}: a = {a:?}, b = {b:?}");
+    }
+  }
+
+  // proofs at a fixed k have a fixed size, and the IPA opening grows a round with each k
+  #[test]
+  fn proof_size_is_stable_and_grows_with_k() {
+    assert_eq!(proof_size(K), proof_size(K));
+    assert!(proof_size(K) < proof_size(K + 1));
+  }
+
+  #[test]
+  fn sweep_reports_each_k_in_order() {
+    let rows = sweep(&[4, 5]);
+    assert_eq!(rows.len(), 2);
+    let ks_and_gates: Vec<_> = rows.iter().map(|&(k, gates, ..)| (k, gates)).collect();
+    assert_eq!(ks_and_gates, [(4, 3), (5, 3)]);
+    assert!(rows[0].2 < rows[1].2, "proof bytes should grow with k");
+  }
+}
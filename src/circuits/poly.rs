@@ -0,0 +1,91 @@
+//! A circuit chaining `depth` multiplications of a private input, exposing x^(depth + 1)
+use halo2_proofs::{
+  circuit::{Layouter, SimpleFloorPlanner, Value},
+  pasta::group::ff::{Field, PrimeField},
+  plonk::{Circuit, ConstraintSystem, Error},
+};
+
+use crate::{
+  gadgets::scalar_mul::{ScalarMulChip, ScalarMulConfig, ScalarMulInstructions},
+  reference,
+};
+
+/// returning x^(depth + 1), to measure how proving time grows with gate count independently of k
+#[derive(Default, Clone)]
+pub struct PolyCircuit<F: Field> {
+  pub x:       Value<F>,
+  /// the number of `mul` gates in the chain
+  pub depth:   usize,
+  /// build the chain with `mul_chained` rather than a `mul` per link
+  pub chained: bool,
+}
+
+impl<F: Field> PolyCircuit<F> {
+  /// Build the circuit for private input `x`, along with its expected public output
+  pub fn new(x: F, depth: usize) -> (Self, F) {
+    let c = reference::pow(x, depth as u64 + 1);
+    (Self { x: Value::known(x), depth, chained: false }, c)
+  }
+
+  /// the number of multiplication gates, one per link of the chain
+  pub fn gate_count(&self) -> u64 { self.depth as u64 }
+}
+
+impl<F: PrimeField> Circuit<F> for PolyCircuit<F> {
+  type Config = ScalarMulConfig;
+  type FloorPlanner = SimpleFloorPlanner;
+
+  // the depth and chaining fix the layout, so keep them when dropping the witness
+  fn without_witnesses(&self) -> Self {
+    Self { depth: self.depth, chained: self.chained, ..Self::default() }
+  }
+
+  fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+    let advice = [meta.advice_column(), meta.advice_column(), meta.advice_column()];
+    let instance = meta.instance_column();
+    let constant = meta.fixed_column();
+    Self::Config::configure(meta, &advice, &[instance], constant)
+  }
+
+  fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+    let field_chip = ScalarMulChip::<F>::new(config);
+
+    let x = field_chip.load_private(layouter.namespace(|| "load x"), self.x)?;
+    let acc = if self.chained {
+      let factors = vec![x.clone(); self.depth];
+      field_chip.mul_chained(layouter.namespace(|| "mul chain"), x, &factors)?
+    } else {
+      let mut acc = x.clone();
+      for i in 0..self.depth {
+        acc = field_chip.mul(layouter.namespace(|| format!("mul {i}")), acc, x.clone())?;
+      }
+      acc
+    };
+
+    field_chip.expose_public(layouter.namespace(|| "expose result"), acc, 0)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use halo2_proofs::pasta::{group::ff::Field, Fp};
+
+  use super::*;
+  use crate::testing::{assert_fails, assert_satisfied};
+
+  const K: u32 = 5;
+
+  #[test]
+  fn exposes_x_to_the_depth_plus_one() {
+    for depth in [1, 5] {
+      let (circuit, c) = PolyCircuit::new(Fp::from(3), depth);
+      assert_eq!(c, Fp::from(3u64.pow(depth as u32 + 1)));
+      assert_satisfied(K, &circuit, vec![vec![c]]);
+      assert_fails(K, &circuit, vec![vec![c + Fp::ONE]]);
+
+      let chained = PolyCircuit { chained: true, ..circuit };
+      assert_satisfied(K, &chained, vec![vec![c]]);
+      assert_fails(K, &chained, vec![vec![c + Fp::ONE]]);
+    }
+  }
+}
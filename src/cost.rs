@@ -0,0 +1,261 @@
+//! Structural cost of a circuit, read off its constraint system without running the prover
+use std::fmt;
+
+use halo2_proofs::{
+  circuit::Value,
+  pasta::{group::ff::Field, Fp},
+  plonk::{
+    Advice, Any, Assigned, Assignment, Circuit, Column, ConstraintSystem, Error, Fixed,
+    FloorPlanner, Instance, Selector,
+  },
+};
+
+/// The shape of a circuit's constraint system at 2^k rows, and how much of it the circuit uses
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CostReport {
+  pub k:           u32,
+  /// rows left for assignment once the blinding rows are reserved
+  pub usable_rows: usize,
+  /// rows the circuit assigns to, out of `usable_rows`
+  pub rows:        usize,
+  /// copy constraints, each a cost in the permutation argument
+  pub copies:      usize,
+  pub advice:      usize,
+  pub fixed:       usize,
+  pub instance:    usize,
+  pub selectors:   usize,
+  pub gates:       usize,
+  pub lookups:     usize,
+  /// the highest degree of any gate or argument, which sets the size of the extended domain
+  pub degree:      usize,
+}
+
+impl fmt::Display for CostReport {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(
+      f,
+      "k={}: {} of {} usable rows, {} copies, {} advice / {} fixed / {} instance columns, {} \
+       selectors, {} gates, {} lookups, degree {}",
+      self.k,
+      self.rows,
+      self.usable_rows,
+      self.copies,
+      self.advice,
+      self.fixed,
+      self.instance,
+      self.selectors,
+      self.gates,
+      self.lookups,
+      self.degree
+    )
+  }
+}
+
+/// The cost of `circuit` at 2^k rows: the shape of its constraint system, and the rows and copies
+/// it uses, from synthesizing it without the prover. Nothing is printed; `CostReport` implements
+/// `Display` for callers that want to
+pub fn describe_cost<C: Circuit<Fp>>(k: u32, circuit: &C) -> CostReport {
+  let (cs, measurement) = measure(circuit);
+
+  CostReport {
+    k,
+    usable_rows: (1 << k) - (cs.blinding_factors() + 1),
+    rows: measurement.rows,
+    copies: measurement.copies,
+    advice: cs.num_advice_columns(),
+    fixed: cs.num_fixed_columns(),
+    instance: cs.num_instance_columns(),
+    selectors: cs.num_selectors(),
+    gates: cs.gates().len(),
+    lookups: cs.lookups().len(),
+    degree: cs.degree(),
+  }
+}
+
+/// The constraint system's degree for `C`: its highest-degree gate or argument. The prover's
+/// extended domain is 2^k times degree - 1, rounded up to a power of two, so 3, the degree of the
+/// `ScalarMulChip` gates' selector * degree-2 expressions, costs the least
+pub fn max_degree<C: Circuit<Fp>>() -> usize {
+  let mut cs = ConstraintSystem::default();
+  C::configure(&mut cs);
+  cs.degree()
+}
+
+/// Synthesize `circuit` and count its copy constraints: every `copy_advice`, `constrain_equal`,
+/// `constrain_instance` and constant assignment, each a cost in the permutation argument
+pub fn copy_count<C: Circuit<Fp>>(circuit: &C) -> usize { measure(circuit).1.copies }
+
+/// The smallest k whose 2^k rows fit everything `circuit` assigns, plus the blinding rows, so
+/// keygen won't fail with `NotEnoughRowsAvailable`
+pub fn min_k_for<C: Circuit<Fp>>(circuit: &C) -> u32 {
+  let (cs, measurement) = measure(circuit);
+  let rows = (measurement.rows + cs.blinding_factors() + 1).max(cs.minimum_rows());
+  rows.next_power_of_two().trailing_zeros()
+}
+
+// configure and synthesize `circuit`, recording what it assigns
+fn measure<C: Circuit<Fp>>(circuit: &C) -> (ConstraintSystem<Fp>, Measurement) {
+  let mut cs = ConstraintSystem::default();
+  let config = C::configure(&mut cs);
+
+  let mut measurement = Measurement::default();
+  C::FloorPlanner::synthesize(&mut measurement, circuit, config, cs.constants().clone())
+    .expect("synthesis should not fail");
+  (cs, measurement)
+}
+
+// an `Assignment` that discards everything but the number of copies and rows used
+#[derive(Default)]
+struct Measurement {
+  copies: usize,
+  rows:   usize,
+}
+
+impl Measurement {
+  fn touch(&mut self, row: usize) { self.rows = self.rows.max(row + 1); }
+}
+
+impl<F: Field> Assignment<F> for Measurement {
+  fn enter_region<NR, N>(&mut self, _: N)
+  where
+    NR: Into<String>,
+    N: FnOnce() -> NR, {
+  }
+
+  fn exit_region(&mut self) {}
+
+  fn enable_selector<A, AR>(&mut self, _: A, _: &Selector, row: usize) -> Result<(), Error>
+  where
+    A: FnOnce() -> AR,
+    AR: Into<String>, {
+    self.touch(row);
+    Ok(())
+  }
+
+  fn query_instance(&self, _: Column<Instance>, _: usize) -> Result<Value<F>, Error> {
+    Ok(Value::unknown())
+  }
+
+  fn assign_advice<V, VR, A, AR>(
+    &mut self,
+    _: A,
+    _: Column<Advice>,
+    row: usize,
+    _: V,
+  ) -> Result<(), Error>
+  where
+    V: FnOnce() -> Value<VR>,
+    VR: Into<Assigned<F>>,
+    A: FnOnce() -> AR,
+    AR: Into<String>,
+  {
+    self.touch(row);
+    Ok(())
+  }
+
+  fn assign_fixed<V, VR, A, AR>(
+    &mut self,
+    _: A,
+    _: Column<Fixed>,
+    row: usize,
+    _: V,
+  ) -> Result<(), Error>
+  where
+    V: FnOnce() -> Value<VR>,
+    VR: Into<Assigned<F>>,
+    A: FnOnce() -> AR,
+    AR: Into<String>,
+  {
+    self.touch(row);
+    Ok(())
+  }
+
+  fn copy(&mut self, _: Column<Any>, _: usize, _: Column<Any>, _: usize) -> Result<(), Error> {
+    self.copies += 1;
+    Ok(())
+  }
+
+  // only pads lookup tables out to the usable rows, which isn't usage
+  fn fill_from_row(
+    &mut self,
+    _: Column<Fixed>,
+    _: usize,
+    _: Value<Assigned<F>>,
+  ) -> Result<(), Error> {
+    Ok(())
+  }
+
+  fn push_namespace<NR, N>(&mut self, _: N)
+  where
+    NR: Into<String>,
+    N: FnOnce() -> NR, {
+  }
+
+  fn pop_namespace(&mut self, _: Option<String>) {}
+}
+
+#[cfg(test)]
+mod tests {
+  use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+  use super::*;
+  use crate::{
+    circuits::nn_mul::NNMulCircuit,
+    reference,
+    testing::{assert_satisfied, Op, OpCircuit},
+  };
+
+  // `MockProver::run` succeeds at the k `min_k_for` returns, and runs out of rows a k below it
+  fn assert_min_k<C: Circuit<Fp>>(circuit: &C, public_inputs: Vec<Vec<Fp>>) -> u32 {
+    let k = min_k_for(circuit);
+    assert_satisfied(k, circuit, public_inputs.clone());
+    let below = MockProver::run(k - 1, circuit, public_inputs);
+    assert!(below.is_err(), "k = {} should have too few rows", k - 1);
+    k
+  }
+
+  #[test]
+  fn describes_nn_mul() {
+    let (circuit, _) = NNMulCircuit::new(Fp::from(2), Fp::from(3));
+    let report = describe_cost(4, &circuit);
+
+    assert_eq!((report.advice, report.fixed, report.instance), (3, 1, 1));
+    // two squares copying one input each, a mul copying two, and the exposed output
+    assert_eq!(report.copies, 5);
+    assert!(0 < report.rows && report.rows <= report.usable_rows, "{report}");
+  }
+
+  #[test]
+  fn square_copies_less_than_mul() {
+    let square = OpCircuit::new(Op::Square, &[Fp::from(3)]);
+    let mul = OpCircuit::new(Op::MulSelf, &[Fp::from(3)]);
+    assert!(copy_count(&square) < copy_count(&mul));
+  }
+
+  #[test]
+  fn min_k_fits_nn_mul() {
+    let (circuit, c) = NNMulCircuit::new(Fp::from(2), Fp::from(3));
+    assert_min_k(&circuit, vec![vec![c]]);
+  }
+
+  #[test]
+  fn min_k_fits_a_long_product() {
+    let inputs = vec![Fp::from(2); 100];
+    let product = inputs.iter().copied().fold(Fp::ONE, reference::mul);
+    let k = assert_min_k(&OpCircuit::new(Op::Product, &inputs), vec![vec![product]]);
+    // a load and a two-row mul per input
+    assert!(k >= 9, "k = {k}");
+  }
+
+  // every basic instruction's gate is a selector times a degree-2 expression
+  #[test]
+  fn basic_instructions_stay_at_degree_3() {
+    assert_eq!(max_degree::<NNMulCircuit<Fp>>(), 3);
+  }
+
+  // what keeps the range table opt-in
+  #[test]
+  fn range_table_raises_the_degree() {
+    assert!(max_degree::<OpCircuit>() > 3);
+  }
+}
@@ -1,29 +1,334 @@
 //! A gadget for a multiplication gate
 use halo2_proofs::{
-  circuit::{AssignedCell, Chip, Layouter, Region, Value},
-  pasta::group::ff::Field,
-  plonk::{Advice, Column, ConstraintSystem, Error, Fixed, Instance, Selector},
+  circuit::{AssignedCell, Cell, Chip, Layouter, Region, Value},
+  pasta::group::ff::{Field, PrimeField},
+  plonk::{
+    Advice, Column, ConstraintSystem, Error, Expression, Fixed, Instance, Selector, TableColumn,
+  },
   poly::Rotation,
 };
 
 pub use self::chip::ScalarMulChip;
 
+/// `range_check_lookup` splits values into limbs of this many bits, each looked up in the range
+/// table
+pub const RANGE_TABLE_BITS: usize = 8;
+
+/// `min` and `max` compare with `less_than` over this many bits, so both inputs must fit in it;
+/// wider inputs make the proof fail rather than compare wrongly
+pub const COMPARISON_BITS: usize = 64;
+
+/// Keygen synthesizes circuits with `Value::unknown()` witnesses, so every instruction must stay in
+/// `Value` space: never unwrap a witness, and only fail on inputs that are known to be invalid.
 pub trait ScalarMulInstructions<F: Field>: Chip<F> {
   type Num;
 
   fn load_private(&self, layouter: impl Layouter<F>, a: Value<F>) -> Result<Self::Num, Error>;
 
+  /// `load_private` for each of `vs`, in one region rather than one each
+  fn load_private_vec(
+    &self,
+    layouter: impl Layouter<F>,
+    vs: &[Value<F>],
+  ) -> Result<Vec<Self::Num>, Error>;
+
+  /// Assign `values` as one contiguous block down the advice column at `column_index`, in a single
+  /// region, so big inputs don't fragment the layout into many small regions
+  fn assign_bulk(
+    &self,
+    layouter: impl Layouter<F>,
+    values: &[Value<F>],
+    column_index: usize,
+  ) -> Result<Vec<Self::Num>, Error>;
+
+  /// `load_private`, also handing back the loaded value for off-circuit checks
+  fn load_private_with_value(
+    &self,
+    layouter: impl Layouter<F>,
+    a: Value<F>,
+  ) -> Result<(Self::Num, Value<F>), Error>;
+
+  /// Load a constant fixed at keygen, through the fixed `constant` column
   fn load_constant(&self, layouter: impl Layouter<F>, constant: F) -> Result<Self::Num, Error>;
 
+  /// `load_constant(F::ZERO)`
+  fn zero(&self, layouter: impl Layouter<F>) -> Result<Self::Num, Error>;
+
+  /// `load_constant(F::ONE)`
+  fn one(&self, layouter: impl Layouter<F>) -> Result<Self::Num, Error>;
+
+  /// Load the public input at `row` of the first instance column, to use it mid-computation
+  fn load_instance(&self, layouter: impl Layouter<F>, row: usize) -> Result<Self::Num, Error>;
+
+  /// Load a public parameter, a constant supplied by the verifier rather than fixed at keygen, from
+  /// `instance_row` of the first instance column
+  fn load_public_constant(
+    &self,
+    layouter: impl Layouter<F>,
+    instance_row: usize,
+  ) -> Result<Self::Num, Error>;
+
   fn mul(&self, layouter: impl Layouter<F>, a: Self::Num, b: Self::Num)
     -> Result<Self::Num, Error>;
 
+  /// Multiply two fresh witnesses, assigned straight into the gate without copy constraints. The
+  /// inputs are not linked to any other cell, so only use this where nothing else refers to them,
+  /// e.g. in isolated benchmark circuits; it saves the permutation cost of `mul`.
+  fn mul_inline(
+    &self,
+    layouter: impl Layouter<F>,
+    a: Value<F>,
+    b: Value<F>,
+  ) -> Result<Self::Num, Error>;
+
+  fn add(&self, layouter: impl Layouter<F>, a: Self::Num, b: Self::Num)
+    -> Result<Self::Num, Error>;
+
+  fn sub(&self, layouter: impl Layouter<F>, a: Self::Num, b: Self::Num)
+    -> Result<Self::Num, Error>;
+
+  fn square(&self, layouter: impl Layouter<F>, a: Self::Num) -> Result<Self::Num, Error>;
+
+  /// A witnessed square root r of `a`, constrained by r * r == a. Which of the two roots, r or -r,
+  /// is unspecified. Errors during synthesis if `a` is known to be a non-residue, having no root
+  fn sqrt(&self, layouter: impl Layouter<F>, a: Self::Num) -> Result<Self::Num, Error>;
+
+  fn neg(&self, layouter: impl Layouter<F>, a: Self::Num) -> Result<Self::Num, Error>;
+
+  fn inverse(&self, layouter: impl Layouter<F>, a: Self::Num) -> Result<Self::Num, Error>;
+
+  fn div(&self, layouter: impl Layouter<F>, a: Self::Num, b: Self::Num)
+    -> Result<Self::Num, Error>;
+
+  fn mul_by_constant(
+    &self,
+    layouter: impl Layouter<F>,
+    a: Self::Num,
+    constant: F,
+  ) -> Result<Self::Num, Error>;
+
+  fn add_constant(
+    &self,
+    layouter: impl Layouter<F>,
+    a: Self::Num,
+    constant: F,
+  ) -> Result<Self::Num, Error>;
+
+  fn mul_add(
+    &self,
+    layouter: impl Layouter<F>,
+    a: Self::Num,
+    b: Self::Num,
+    c: Self::Num,
+  ) -> Result<Self::Num, Error>;
+
+  fn mul_many(
+    &self,
+    layouter: impl Layouter<F>,
+    a: &[Self::Num],
+    b: &[Self::Num],
+  ) -> Result<Vec<Self::Num>, Error>;
+
+  fn dot(
+    &self,
+    layouter: impl Layouter<F>,
+    a: &[Self::Num],
+    b: &[Self::Num],
+  ) -> Result<Self::Num, Error>;
+
+  fn sum(&self, layouter: impl Layouter<F>, xs: &[Self::Num]) -> Result<Self::Num, Error>;
+
+  fn product(&self, layouter: impl Layouter<F>, xs: &[Self::Num]) -> Result<Self::Num, Error>;
+
+  fn assert_bool(&self, layouter: impl Layouter<F>, a: Self::Num) -> Result<(), Error>;
+
+  fn select(
+    &self,
+    layouter: impl Layouter<F>,
+    cond: Self::Num,
+    a: Self::Num,
+    b: Self::Num,
+  ) -> Result<Self::Num, Error>;
+
+  fn is_zero(&self, layouter: impl Layouter<F>, a: Self::Num) -> Result<Self::Num, Error>;
+
+  fn is_equal(
+    &self,
+    layouter: impl Layouter<F>,
+    a: Self::Num,
+    b: Self::Num,
+  ) -> Result<Self::Num, Error>;
+
+  fn assert_equal(
+    &self,
+    layouter: impl Layouter<F>,
+    a: Self::Num,
+    b: Self::Num,
+  ) -> Result<(), Error>;
+
+  /// Pin `a` to `constant`, fixed at keygen; unlike `load_constant`, no new value is produced
+  fn assert_equal_constant(
+    &self,
+    layouter: impl Layouter<F>,
+    a: Self::Num,
+    constant: F,
+  ) -> Result<(), Error>;
+
+  fn pow(&self, layouter: impl Layouter<F>, base: Self::Num, exp: u64) -> Result<Self::Num, Error>;
+
+  fn range_check(
+    &self,
+    layouter: impl Layouter<F>,
+    a: Self::Num,
+    n_bits: usize,
+  ) -> Result<(), Error>;
+
+  fn to_bits(
+    &self,
+    layouter: impl Layouter<F>,
+    a: Self::Num,
+    n_bits: usize,
+  ) -> Result<Vec<Self::Num>, Error>;
+
+  fn scalar_mul_bits(
+    &self,
+    layouter: impl Layouter<F>,
+    base: Self::Num,
+    scalar_bits: &[Self::Num],
+  ) -> Result<Self::Num, Error>;
+
+  /// Fill the range table with `0..2^RANGE_TABLE_BITS`; call once per circuit before
+  /// `range_check_lookup`, with k large enough to fit the table. Needs a config built
+  /// `with_range_table`
+  fn load_range_table(&self, layouter: impl Layouter<F>) -> Result<(), Error>;
+
+  /// `range_check` by lookups of `RANGE_TABLE_BITS`-bit limbs rather than one row per bit;
+  /// `n_bits` must be a multiple of `RANGE_TABLE_BITS`. Needs a config built `with_range_table`
+  fn range_check_lookup(
+    &self,
+    layouter: impl Layouter<F>,
+    a: Self::Num,
+    n_bits: usize,
+  ) -> Result<(), Error>;
+
+  /// 1 iff `a < b`, for `a` and `b` that fit in `n_bits` bits; bounding the inputs keeps them clear
+  /// of wraparound in the field, where every element is "less than" every other. Both inputs are
+  /// range-checked, so wider ones make the proof fail rather than compare wrongly
+  fn less_than(
+    &self,
+    layouter: impl Layouter<F>,
+    a: Self::Num,
+    b: Self::Num,
+    n_bits: usize,
+  ) -> Result<Self::Num, Error>;
+
+  /// the larger of `a` and `b`, both assumed to fit in `COMPARISON_BITS` bits
+  fn max(&self, layouter: impl Layouter<F>, a: Self::Num, b: Self::Num)
+    -> Result<Self::Num, Error>;
+
+  /// the smaller of `a` and `b`, both assumed to fit in `COMPARISON_BITS` bits
+  fn min(&self, layouter: impl Layouter<F>, a: Self::Num, b: Self::Num)
+    -> Result<Self::Num, Error>;
+
+  /// max(a, 0), reading `a` as signed: in (-2^n_bits, 2^n_bits), with negatives as p - |a|
+  fn relu(
+    &self,
+    layouter: impl Layouter<F>,
+    a: Self::Num,
+    n_bits: usize,
+  ) -> Result<Self::Num, Error>;
+
+  /// a * factors[0] * factors[1] * ..., in one region where each product lands in the next
+  /// gate's lhs cell, so only the factors are copied in, not the running product
+  fn mul_chained(
+    &self,
+    layouter: impl Layouter<F>,
+    a: Self::Num,
+    factors: &[Self::Num],
+  ) -> Result<Self::Num, Error>;
+
+  /// ((init * leaves[0]) * leaves[1]) * ..., one `mul` region per leaf; returns the final
+  /// accumulator along with every intermediate one, for debugging
+  fn fold_mul(
+    &self,
+    layouter: impl Layouter<F>,
+    init: Self::Num,
+    leaves: &[Self::Num],
+  ) -> Result<(Self::Num, Vec<Self::Num>), Error>;
+
+  /// `prev_total + addend`, one step of a running total, for online aggregation
+  fn accumulate(
+    &self,
+    layouter: impl Layouter<F>,
+    prev_total: Self::Num,
+    addend: Self::Num,
+  ) -> Result<Self::Num, Error>;
+
+  /// sum(coeffs[i] * x^i) by Horner's rule, one `mul_add` per coefficient past the leading one
+  fn horner(
+    &self,
+    layouter: impl Layouter<F>,
+    coeffs: &[Self::Num],
+    x: Self::Num,
+  ) -> Result<Self::Num, Error>;
+
+  /// if `cond` then `val` must be zero, i.e. cond * val == 0, with `cond` constrained boolean
+  fn assert_zero_if(
+    &self,
+    layouter: impl Layouter<F>,
+    cond: Self::Num,
+    val: Self::Num,
+  ) -> Result<(), Error>;
+
+  /// `a` must be nonzero, by witnessing its inverse; the dual of `assert_zero_if`. Errors during
+  /// synthesis, rather than leaving the circuit unsatisfiable, if `a` is known to be zero
+  fn assert_nonzero(&self, layouter: impl Layouter<F>, a: Self::Num) -> Result<(), Error>;
+
   fn expose_public(
     &self,
     layouter: impl Layouter<F>,
     num: Self::Num,
     row: usize,
   ) -> Result<(), Error>;
+
+  /// `expose_public` to the instance column at index `column` of those passed to `configure`, for
+  /// circuits grouping public outputs by meaning
+  fn expose_public_in(
+    &self,
+    layouter: impl Layouter<F>,
+    num: Self::Num,
+    column: usize,
+    row: usize,
+  ) -> Result<(), Error>;
+
+  /// Expose `nums` to consecutive rows of the first instance column from `start_row`. Fails with
+  /// `InstanceTooLarge`, before exposing any, if they'd run past `instance_rows`: the column's
+  /// length, which can be no more than its usable rows
+  fn expose_public_vec(
+    &self,
+    layouter: impl Layouter<F>,
+    nums: &[Self::Num],
+    start_row: usize,
+    instance_rows: usize,
+  ) -> Result<(), Error>;
+
+  /// Square `a` and expose the result at `row` of the first instance column
+  fn square_and_expose(
+    &self,
+    layouter: impl Layouter<F>,
+    a: Self::Num,
+    row: usize,
+  ) -> Result<(), Error>;
+
+  /// Expose the result of any instruction at `row` of the first instance column. Both calls borrow
+  /// the layouter, so bind the result first: `let c = chip.mul(layouter.namespace(..), a, b)?;`
+  /// then `chip.compute_and_expose(layouter.namespace(..), c, 0)`
+  fn compute_and_expose(
+    &self,
+    layouter: impl Layouter<F>,
+    num: Self::Num,
+    row: usize,
+  ) -> Result<(), Error>;
 }
 
 #[derive(Clone, Debug)]
@@ -31,7 +336,40 @@ pub trait ScalarMulInstructions<F: Field>: Chip<F> {
 // Represent a value at a cell
 pub struct Number<F: Field>(AssignedCell<F, F>);
 
-impl<F: Field> ScalarMulInstructions<F> for ScalarMulChip<F> {
+impl<F: Field> Number<F> {
+  pub fn value(&self) -> Value<F> { self.0.value().copied() }
+
+  pub fn cell(&self) -> Cell { self.0.cell() }
+}
+
+impl<F: Field> From<AssignedCell<F, F>> for Number<F> {
+  fn from(cell: AssignedCell<F, F>) -> Self { Number(cell) }
+}
+
+/// Exposes `Number`s to consecutive rows of an instance column, so multi-output circuits needn't
+/// track the next free row by hand
+#[derive(Clone, Debug)]
+pub struct InstanceWriter {
+  column:   Column<Instance>,
+  next_row: usize,
+}
+
+impl InstanceWriter {
+  /// Start at row 0 of `column`, which must have equality enabled, e.g. one of `config.instance`
+  pub fn new(column: Column<Instance>) -> Self { Self { column, next_row: 0 } }
+
+  pub fn expose_next<F: Field>(
+    &mut self,
+    mut layouter: impl Layouter<F>,
+    num: Number<F>,
+  ) -> Result<(), Error> {
+    layouter.constrain_instance(num.cell(), self.column, self.next_row)?;
+    self.next_row += 1;
+    Ok(())
+  }
+}
+
+impl<F: PrimeField> ScalarMulInstructions<F> for ScalarMulChip<F> {
   type Num = Number<F>;
 
   // load the private input
@@ -50,6 +388,45 @@ impl<F: Field> ScalarMulInstructions<F> for ScalarMulChip<F> {
     )
   }
 
+  fn load_private_vec(
+    &self,
+    layouter: impl Layouter<F>,
+    vs: &[Value<F>],
+  ) -> Result<Vec<Self::Num>, Error> {
+    self.assign_bulk(layouter, vs, 0)
+  }
+
+  fn assign_bulk(
+    &self,
+    mut layouter: impl Layouter<F>,
+    values: &[Value<F>],
+    column_index: usize,
+  ) -> Result<Vec<Self::Num>, Error> {
+    let config = self.config();
+    let column = *config.advice.get(column_index).ok_or(Error::Synthesis)?;
+
+    layouter.assign_region(
+      || "assign bulk",
+      |mut region| {
+        values
+          .iter()
+          .enumerate()
+          .map(|(i, v)| region.assign_advice(|| format!("private input {i}"), column, i, || *v))
+          .map(|cell| cell.map(Number))
+          .collect()
+      },
+    )
+  }
+
+  fn load_private_with_value(
+    &self,
+    layouter: impl Layouter<F>,
+    a: Value<F>,
+  ) -> Result<(Self::Num, Value<F>), Error> {
+    let num = self.load_private(layouter, a)?;
+    Ok((num, a))
+  }
+
   // load the constant
   fn load_constant(&self, mut layouter: impl Layouter<F>, constant: F) -> Result<Self::Num, Error> {
     let config = self.config();
@@ -64,6 +441,42 @@ impl<F: Field> ScalarMulInstructions<F> for ScalarMulChip<F> {
     )
   }
 
+  fn zero(&self, layouter: impl Layouter<F>) -> Result<Self::Num, Error> {
+    self.load_constant(layouter, F::ZERO)
+  }
+
+  fn one(&self, layouter: impl Layouter<F>) -> Result<Self::Num, Error> {
+    self.load_constant(layouter, F::ONE)
+  }
+
+  fn load_instance(&self, mut layouter: impl Layouter<F>, row: usize) -> Result<Self::Num, Error> {
+    let config = self.config();
+
+    layouter.assign_region(
+      || "load instance",
+      |mut region| {
+        region
+          .assign_advice_from_instance(
+            || "public input",
+            config.instance[0],
+            row,
+            config.advice[0],
+            0,
+          )
+          .map(Number)
+      },
+    )
+  }
+
+  fn load_public_constant(
+    &self,
+    layouter: impl Layouter<F>,
+    instance_row: usize,
+  ) -> Result<Self::Num, Error> {
+    // a public constant is just a public input that the circuit never varies
+    self.load_instance(layouter, instance_row)
+  }
+
   fn mul(
     &self,
     mut layouter: impl Layouter<F>,
@@ -76,16 +489,17 @@ impl<F: Field> ScalarMulInstructions<F> for ScalarMulChip<F> {
       || "mul",
       |mut region: Region<'_, F>| {
         // We only want to use a single multiplication gate in this region,
-        // so we enable it at region offset 0; this means it will constrain
-        // cells at offsets 0 and 1.
-        config.s_mul.enable(&mut region, 0)?;
+        // so we enable it once; the layout decides at which offset, and which
+        // cells it then constrains.
+        let (offset, [lhs, rhs, out]) = config.mul_cells(0);
+        config.s_mul.enable(&mut region, offset)?;
 
         // The inputs we've been given could be located anywhere in the circuit,
         // but we can only rely on relative offsets inside this region. So we
         // assign new cells inside the region and constrain them to have the
         // same values as the inputs.
-        a.0.copy_advice(|| "lhs", &mut region, config.advice[0], 0)?;
-        b.0.copy_advice(|| "rhs", &mut region, config.advice[1], 0)?;
+        a.0.copy_advice(|| "lhs", &mut region, lhs.0, lhs.1)?;
+        b.0.copy_advice(|| "rhs", &mut region, rhs.0, rhs.1)?;
 
         // Now we can assign the multiplication result, which is to be assigned
         // into the output position.
@@ -93,81 +507,1258 @@ impl<F: Field> ScalarMulInstructions<F> for ScalarMulChip<F> {
 
         // Finally, we do the assignment to the output, returning a
         // variable to be used in another part of the circuit.
-        region.assign_advice(|| "lhs * rhs", config.advice[0], 1, || value).map(Number)
+        region.assign_advice(|| "lhs * rhs", out.0, out.1, || value).map(Number)
       },
     )
   }
 
-  fn expose_public(
+  fn mul_inline(
     &self,
     mut layouter: impl Layouter<F>,
-    num: Self::Num,
-    row: usize,
-  ) -> Result<(), Error> {
+    a: Value<F>,
+    b: Value<F>,
+  ) -> Result<Self::Num, Error> {
     let config = self.config();
 
-    layouter.constrain_instance(num.0.cell(), config.instance, row)
-  }
-}
+    layouter.assign_region(
+      || "mul inline",
+      |mut region: Region<'_, F>| {
+        let (offset, [lhs, rhs, out]) = config.mul_cells(0);
+        config.s_mul.enable(&mut region, offset)?;
 
-#[derive(Clone, Debug)]
-pub struct ScalarMulConfig {
-  pub advice:   [Column<Advice>; 2],
-  pub instance: Column<Instance>,
-  pub s_mul:    Selector,
-}
+        // unlike `mul`, assign the inputs directly rather than copying them in
+        region.assign_advice(|| "lhs", lhs.0, lhs.1, || a)?;
+        region.assign_advice(|| "rhs", rhs.0, rhs.1, || b)?;
 
-impl ScalarMulConfig {
-  pub fn configure<F: Field>(
-    meta: &mut ConstraintSystem<F>,
-    advice: [Column<Advice>; 2],
-    instance: Column<Instance>,
-    constant: Column<Fixed>,
-  ) -> Self {
-    // specify the columns that can be compared used by the constraint system
-    meta.enable_equality(instance);
-    meta.enable_constant(constant);
-    for column in &advice {
-      meta.enable_equality(*column);
-    }
+        let value = a * b;
+        region.assign_advice(|| "lhs * rhs", out.0, out.1, || value).map(Number)
+      },
+    )
+  }
 
-    // meta selector is used to enable gates
-    let s_mul = meta.selector();
+  fn add(
+    &self,
+    mut layouter: impl Layouter<F>,
+    a: Self::Num,
+    b: Self::Num,
+  ) -> Result<Self::Num, Error> {
+    let config = self.config();
 
-    // Define our multiplication gate
-    meta.create_gate("mul", |meta| {
-      // To implement multiplication, we need three advice cells and a selector
-      // cell. We arrange them like so:
-      //
-      // | a0  | a1  | s_mul |
-      // |-----|-----|-------|
-      // | lhs | rhs | s_mul |
-      // | out |     |       |
-      //
-      // Gates may refer to any relative offsets we want, but each distinct
-      // offset adds a cost to the proof. The most common offsets are 0 (the
-      // current row), 1 (the next row), and -1 (the previous row), for which
-      // `Rotation` has specific constructors.
-      let lhs = meta.query_advice(advice[0], Rotation::cur());
-      let rhs = meta.query_advice(advice[1], Rotation::cur());
-      let out = meta.query_advice(advice[0], Rotation::next());
-      let s_mul = meta.query_selector(s_mul);
+    layouter.assign_region(
+      || "add",
+      |mut region: Region<'_, F>| {
+        // same layout as the default `mul`: inputs at offset 0, output at offset 1
+        config.s_add.enable(&mut region, 0)?;
 
-      // Finally, we return the polynomial expressions that constrain this gate.
-      // For our multiplication gate, we only need a single polynomial constraint.
-      //
-      // The polynomial expressions returned from `create_gate` will be
-      // constrained by the proving system to equal zero. Our expression
-      // has the following properties:
-      // - When s_mul = 0, any value is allowed in lhs, rhs, and out.
-      // - When s_mul != 0, this constrains lhs * rhs = out.
-      vec![s_mul * (lhs * rhs - out)]
-      // vec![Expression::Constant(F::ZERO)]
-    });
+        a.0.copy_advice(|| "lhs", &mut region, config.advice[0], 0)?;
+        b.0.copy_advice(|| "rhs", &mut region, config.advice[1], 0)?;
 
-    ScalarMulConfig { advice, instance, s_mul }
-  }
-}
+        let value = a.0.value().copied() + b.0.value();
+
+        region.assign_advice(|| "lhs + rhs", config.advice[0], 1, || value).map(Number)
+      },
+    )
+  }
+
+  fn sub(
+    &self,
+    mut layouter: impl Layouter<F>,
+    a: Self::Num,
+    b: Self::Num,
+  ) -> Result<Self::Num, Error> {
+    let config = self.config();
+
+    layouter.assign_region(
+      || "sub",
+      |mut region: Region<'_, F>| {
+        config.s_sub.enable(&mut region, 0)?;
+
+        a.0.copy_advice(|| "lhs", &mut region, config.advice[0], 0)?;
+        b.0.copy_advice(|| "rhs", &mut region, config.advice[1], 0)?;
+
+        let value = a.0.value().copied() - b.0.value();
+
+        region.assign_advice(|| "lhs - rhs", config.advice[0], 1, || value).map(Number)
+      },
+    )
+  }
+
+  fn square(&self, mut layouter: impl Layouter<F>, a: Self::Num) -> Result<Self::Num, Error> {
+    let config = self.config();
+
+    layouter.assign_region(
+      || "square",
+      |mut region: Region<'_, F>| {
+        config.s_sq.enable(&mut region, 0)?;
+
+        // only one copy constraint, as opposed to `mul(a.clone(), a)`
+        a.0.copy_advice(|| "lhs", &mut region, config.advice[0], 0)?;
+
+        let value = a.0.value().map(|a| a.square());
+
+        region.assign_advice(|| "lhs * lhs", config.advice[0], 1, || value).map(Number)
+      },
+    )
+  }
+
+  // the square gate run backwards: witness the root as its input and copy `a` in as its output
+  fn sqrt(&self, mut layouter: impl Layouter<F>, a: Self::Num) -> Result<Self::Num, Error> {
+    let config = self.config();
+
+    // a non-residue has no root; refuse rather than witness a value the gate can't satisfy
+    a.0.value().error_if_known_and(|a| bool::from(a.sqrt().is_none()))?;
+
+    layouter.assign_region(
+      || "sqrt",
+      |mut region: Region<'_, F>| {
+        config.s_sq.enable(&mut region, 0)?;
+
+        let value = a.0.value().map(|a| a.sqrt().unwrap_or(F::ZERO));
+        let root = region.assign_advice(|| "sqrt(out)", config.advice[0], 0, || value)?;
+
+        a.0.copy_advice(|| "out", &mut region, config.advice[0], 1)?;
+        Ok(Number(root))
+      },
+    )
+  }
+
+  fn neg(&self, mut layouter: impl Layouter<F>, a: Self::Num) -> Result<Self::Num, Error> {
+    let config = self.config();
+
+    layouter.assign_region(
+      || "neg",
+      |mut region: Region<'_, F>| {
+        // a one-row gate: the output sits next to the input rather than below it
+        config.s_neg.enable(&mut region, 0)?;
+
+        a.0.copy_advice(|| "lhs", &mut region, config.advice[0], 0)?;
+
+        let value = a.0.value().map(|a| -*a);
+
+        region.assign_advice(|| "-lhs", config.advice[1], 0, || value).map(Number)
+      },
+    )
+  }
+
+  // witness the inverse of `a`, failing synthesis if `a` is known to be zero
+  fn inverse(&self, mut layouter: impl Layouter<F>, a: Self::Num) -> Result<Self::Num, Error> {
+    let config = self.config();
+
+    // zero has no inverse; refuse rather than witness a value the gate can't satisfy
+    a.0.value().error_if_known_and(|a| a.is_zero_vartime())?;
+
+    layouter.assign_region(
+      || "inverse",
+      |mut region: Region<'_, F>| {
+        config.s_inv.enable(&mut region, 0)?;
+
+        a.0.copy_advice(|| "lhs", &mut region, config.advice[0], 0)?;
+
+        let value = a.0.value().map(|a| a.invert().unwrap_or(F::ZERO));
+
+        region.assign_advice(|| "1 / lhs", config.advice[1], 0, || value).map(Number)
+      },
+    )
+  }
+
+  // compute a / b in a single gate, without materializing 1 / b
+  fn div(
+    &self,
+    mut layouter: impl Layouter<F>,
+    a: Self::Num,
+    b: Self::Num,
+  ) -> Result<Self::Num, Error> {
+    let config = self.config();
+
+    // division by zero is unsatisfiable, so bail out before assigning anything
+    b.0.value().error_if_known_and(|b| b.is_zero_vartime())?;
+
+    layouter.assign_region(
+      || "div",
+      |mut region: Region<'_, F>| {
+        config.s_div.enable(&mut region, 0)?;
+
+        a.0.copy_advice(|| "lhs", &mut region, config.advice[0], 0)?;
+        b.0.copy_advice(|| "rhs", &mut region, config.advice[1], 0)?;
+
+        let value = a.0.value().zip(b.0.value()).map(|(a, b)| *a * b.invert().unwrap_or(F::ZERO));
+
+        region.assign_advice(|| "lhs / rhs", config.advice[0], 1, || value).map(Number)
+      },
+    )
+  }
+
+  // multiply by a constant held in the fixed column, leaving the second advice column free
+  fn mul_by_constant(
+    &self,
+    mut layouter: impl Layouter<F>,
+    a: Self::Num,
+    constant: F,
+  ) -> Result<Self::Num, Error> {
+    let config = self.config();
+
+    layouter.assign_region(
+      || "mul by constant",
+      |mut region: Region<'_, F>| {
+        config.s_cmul.enable(&mut region, 0)?;
+
+        a.0.copy_advice(|| "lhs", &mut region, config.advice[0], 0)?;
+        region.assign_fixed(|| "constant", config.constant, 0, || Value::known(constant))?;
+
+        let value = a.0.value().map(|a| *a * constant);
+
+        region.assign_advice(|| "lhs * constant", config.advice[0], 1, || value).map(Number)
+      },
+    )
+  }
+
+  // add a constant held in the fixed column, leaving the second advice column free
+  fn add_constant(
+    &self,
+    mut layouter: impl Layouter<F>,
+    a: Self::Num,
+    constant: F,
+  ) -> Result<Self::Num, Error> {
+    let config = self.config();
+
+    layouter.assign_region(
+      || "add constant",
+      |mut region: Region<'_, F>| {
+        config.s_addc.enable(&mut region, 0)?;
+
+        a.0.copy_advice(|| "lhs", &mut region, config.advice[0], 0)?;
+        region.assign_fixed(|| "constant", config.constant, 0, || Value::known(constant))?;
+
+        let value = a.0.value().map(|a| *a + constant);
+
+        region.assign_advice(|| "lhs + constant", config.advice[0], 1, || value).map(Number)
+      },
+    )
+  }
+
+  // compute a * b + c in a single region
+  fn mul_add(
+    &self,
+    mut layouter: impl Layouter<F>,
+    a: Self::Num,
+    b: Self::Num,
+    c: Self::Num,
+  ) -> Result<Self::Num, Error> {
+    let config = self.config();
+    // only registered when the chip was configured with a third advice column
+    let s_muladd = config.s_muladd.ok_or(Error::Synthesis)?;
+
+    layouter.assign_region(
+      || "mul add",
+      |mut region: Region<'_, F>| {
+        s_muladd.enable(&mut region, 0)?;
+
+        a.0.copy_advice(|| "a", &mut region, config.advice[0], 0)?;
+        b.0.copy_advice(|| "b", &mut region, config.advice[1], 0)?;
+        c.0.copy_advice(|| "c", &mut region, config.advice[2], 0)?;
+
+        let value = a.0.value().copied() * b.0.value() + c.0.value();
+
+        region.assign_advice(|| "a * b + c", config.advice[0], 1, || value).map(Number)
+      },
+    )
+  }
+
+  // element-wise product of two equal-length slices, one `mul` region per pair
+  fn mul_many(
+    &self,
+    mut layouter: impl Layouter<F>,
+    a: &[Self::Num],
+    b: &[Self::Num],
+  ) -> Result<Vec<Self::Num>, Error> {
+    if a.len() != b.len() {
+      return Err(Error::Synthesis);
+    }
+
+    a.iter()
+      .zip(b)
+      .enumerate()
+      .map(|(i, (a, b))| self.mul(layouter.namespace(|| format!("mul {i}")), a.clone(), b.clone()))
+      .collect()
+  }
+
+  // inner product, folding each pair into a running sum with `mul_add`
+  fn dot(
+    &self,
+    mut layouter: impl Layouter<F>,
+    a: &[Self::Num],
+    b: &[Self::Num],
+  ) -> Result<Self::Num, Error> {
+    if a.len() != b.len() {
+      return Err(Error::Synthesis);
+    }
+
+    let mut pairs = a.iter().zip(b);
+    let mut acc = match pairs.next() {
+      Some((a, b)) => self.mul(layouter.namespace(|| "dot 0"), a.clone(), b.clone())?,
+      None => return self.zero(layouter.namespace(|| "dot of empty")),
+    };
+    for (i, (a, b)) in pairs.enumerate() {
+      let mut layouter = layouter.namespace(|| format!("dot {}", i + 1));
+      acc = match self.config().s_muladd {
+        Some(_) => self.mul_add(layouter, a.clone(), b.clone(), acc)?,
+        // without a third advice column, fall back to a separate mul and add
+        None => {
+          let ab = self.mul(layouter.namespace(|| "a * b"), a.clone(), b.clone())?;
+          self.add(layouter.namespace(|| "acc + a * b"), acc, ab)?
+        },
+      };
+    }
+
+    Ok(acc)
+  }
+
+  // fold the inputs into a single chain of `add` regions
+  fn sum(&self, mut layouter: impl Layouter<F>, xs: &[Self::Num]) -> Result<Self::Num, Error> {
+    let (first, rest) = match xs.split_first() {
+      Some(split) => split,
+      None => return self.zero(layouter.namespace(|| "sum of empty")),
+    };
+
+    rest.iter().enumerate().try_fold(first.clone(), |acc, (i, x)| {
+      self.add(layouter.namespace(|| format!("sum {}", i + 1)), acc, x.clone())
+    })
+  }
+
+  // fold the inputs into a single chain of `mul` regions
+  fn product(&self, mut layouter: impl Layouter<F>, xs: &[Self::Num]) -> Result<Self::Num, Error> {
+    let (first, rest) = match xs.split_first() {
+      Some(split) => split,
+      None => return self.one(layouter.namespace(|| "product of empty")),
+    };
+
+    rest.iter().enumerate().try_fold(first.clone(), |acc, (i, x)| {
+      self.mul(layouter.namespace(|| format!("product {}", i + 1)), acc, x.clone())
+    })
+  }
+
+  // constrain `a` to be 0 or 1; the caller keeps using the original `a`
+  fn assert_bool(&self, mut layouter: impl Layouter<F>, a: Self::Num) -> Result<(), Error> {
+    let config = self.config();
+
+    layouter.assign_region(
+      || "assert bool",
+      |mut region: Region<'_, F>| {
+        config.s_bool.enable(&mut region, 0)?;
+
+        a.0.copy_advice(|| "bit", &mut region, config.advice[0], 0)?;
+
+        Ok(())
+      },
+    )
+  }
+
+  // cond ? a : b, constraining `cond` to be boolean first
+  fn select(
+    &self,
+    mut layouter: impl Layouter<F>,
+    cond: Self::Num,
+    a: Self::Num,
+    b: Self::Num,
+  ) -> Result<Self::Num, Error> {
+    let config = self.config();
+    // only registered when the chip was configured with a third advice column
+    let s_sel = config.s_sel.ok_or(Error::Synthesis)?;
+
+    self.assert_bool(layouter.namespace(|| "cond is bool"), cond.clone())?;
+
+    layouter.assign_region(
+      || "select",
+      |mut region: Region<'_, F>| {
+        s_sel.enable(&mut region, 0)?;
+
+        cond.0.copy_advice(|| "cond", &mut region, config.advice[0], 0)?;
+        a.0.copy_advice(|| "a", &mut region, config.advice[1], 0)?;
+        b.0.copy_advice(|| "b", &mut region, config.advice[2], 0)?;
+
+        let value =
+          cond.0.value().zip(a.0.value()).zip(b.0.value()).map(|((c, a), b)| *c * (*a - b) + b);
+
+        region.assign_advice(|| "cond ? a : b", config.advice[0], 1, || value).map(Number)
+      },
+    )
+  }
+
+  // 1 if `a` is zero, 0 otherwise, via a witnessed inverse
+  fn is_zero(&self, mut layouter: impl Layouter<F>, a: Self::Num) -> Result<Self::Num, Error> {
+    let config = self.config();
+
+    layouter.assign_region(
+      || "is zero",
+      |mut region: Region<'_, F>| {
+        config.s_iz.enable(&mut region, 0)?;
+
+        a.0.copy_advice(|| "a", &mut region, config.advice[0], 0)?;
+
+        // zero has no inverse, so witness 0 in that case; `out` then comes out as 1
+        let a_inv = a.0.value().map(|a| a.invert().unwrap_or(F::ZERO));
+        region.assign_advice(|| "a_inv", config.advice[1], 0, || a_inv)?;
+
+        let value = a.0.value().zip(a_inv).map(|(a, a_inv)| F::ONE - *a * a_inv);
+
+        region.assign_advice(|| "a == 0", config.advice[0], 1, || value).map(Number)
+      },
+    )
+  }
+
+  // 1 if a == b, 0 otherwise
+  fn is_equal(
+    &self,
+    mut layouter: impl Layouter<F>,
+    a: Self::Num,
+    b: Self::Num,
+  ) -> Result<Self::Num, Error> {
+    let diff = self.sub(layouter.namespace(|| "a - b"), a, b)?;
+    self.is_zero(layouter.namespace(|| "a - b == 0"), diff)
+  }
+
+  // a copy constraint between the two cells; no gate needed
+  fn assert_equal(
+    &self,
+    mut layouter: impl Layouter<F>,
+    a: Self::Num,
+    b: Self::Num,
+  ) -> Result<(), Error> {
+    layouter.assign_region(
+      || "assert equal",
+      |mut region: Region<'_, F>| region.constrain_equal(a.0.cell(), b.0.cell()),
+    )
+  }
+
+  fn assert_equal_constant(
+    &self,
+    mut layouter: impl Layouter<F>,
+    a: Self::Num,
+    constant: F,
+  ) -> Result<(), Error> {
+    // copies `a` straight to the constant in the fixed column, without an advice cell in between
+    layouter.assign_region(
+      || "assert equal constant",
+      |mut region: Region<'_, F>| region.constrain_constant(a.0.cell(), constant),
+    )
+  }
+
+  // base^exp by left-to-right square-and-multiply, about 2 * log2(exp) regions
+  fn pow(
+    &self,
+    mut layouter: impl Layouter<F>,
+    base: Self::Num,
+    exp: u64,
+  ) -> Result<Self::Num, Error> {
+    if exp == 0 {
+      return self.one(layouter.namespace(|| "base^0"));
+    }
+
+    // the leading bit is consumed by starting the accumulator at `base`
+    let n_bits = u64::BITS - exp.leading_zeros();
+    let mut acc = base.clone();
+    for i in (0..n_bits - 1).rev() {
+      acc = self.square(layouter.namespace(|| format!("square {i}")), acc)?;
+      if (exp >> i) & 1 == 1 {
+        acc = self.mul(layouter.namespace(|| format!("mul {i}")), acc, base.clone())?;
+      }
+    }
+
+    Ok(acc)
+  }
+
+  // constrain `a` to fit in `n_bits` bits; too-large values fail at verify time, not synthesis
+  fn range_check(
+    &self,
+    layouter: impl Layouter<F>,
+    a: Self::Num,
+    n_bits: usize,
+  ) -> Result<(), Error> {
+    self.decompose(layouter, &a, n_bits).map(|_| ())
+  }
+
+  // little-endian boolean cells of `a`, each constrained boolean and recomposing to `a`
+  fn to_bits(
+    &self,
+    layouter: impl Layouter<F>,
+    a: Self::Num,
+    n_bits: usize,
+  ) -> Result<Vec<Self::Num>, Error> {
+    self.decompose(layouter, &a, n_bits)
+  }
+
+  // scalar * base by double-and-add over little-endian `scalar_bits`, which the caller must have
+  // constrained boolean, e.g. via `to_bits`
+  fn scalar_mul_bits(
+    &self,
+    mut layouter: impl Layouter<F>,
+    base: Self::Num,
+    scalar_bits: &[Self::Num],
+  ) -> Result<Self::Num, Error> {
+    let mut bits = scalar_bits.iter().rev();
+    let mut acc = match bits.next() {
+      Some(msb) => self.mul(layouter.namespace(|| "msb * base"), msb.clone(), base.clone())?,
+      None => return self.zero(layouter.namespace(|| "empty scalar")),
+    };
+    for (i, bit) in bits.enumerate() {
+      let mut layouter = layouter.namespace(|| format!("double and add {i}"));
+      let double = self.add(layouter.namespace(|| "acc + acc"), acc.clone(), acc)?;
+      acc = self.mul_add(
+        layouter.namespace(|| "bit * base + 2 * acc"),
+        bit.clone(),
+        base.clone(),
+        double,
+      )?;
+    }
+
+    Ok(acc)
+  }
+
+  fn load_range_table(&self, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+    let config = self.config();
+    let range_table = config.range_table.ok_or(Error::Synthesis)?;
+
+    layouter.assign_table(
+      || "range table",
+      |mut table| {
+        for i in 0..1 << RANGE_TABLE_BITS {
+          let value = Value::known(F::from(i as u64));
+          table.assign_cell(|| "range value", range_table, i, || value)?;
+        }
+        Ok(())
+      },
+    )
+  }
+
+  fn range_check_lookup(
+    &self,
+    mut layouter: impl Layouter<F>,
+    a: Self::Num,
+    n_bits: usize,
+  ) -> Result<(), Error> {
+    let config = self.config();
+    let s_limbs = config.s_limbs.ok_or(Error::Synthesis)?;
+
+    // a partial top limb would need a narrower table; and leave room below the modulus
+    if n_bits % RANGE_TABLE_BITS != 0 || n_bits >= F::NUM_BITS as usize {
+      return Err(Error::Synthesis);
+    }
+    let n_limbs = n_bits / RANGE_TABLE_BITS;
+
+    layouter.assign_region(
+      || "range check lookup",
+      |mut region: Region<'_, F>| {
+        // same running sum as `decompose`, but shifting by a limb per row rather than a bit
+        let mut acc = region.assign_advice_from_constant(|| "acc", config.advice[1], 0, F::ZERO)?;
+        let shift = F::from(1 << RANGE_TABLE_BITS);
+
+        for i in (0..n_limbs).rev() {
+          let offset = n_limbs - i;
+          s_limbs.enable(&mut region, offset)?;
+
+          let limb = a.0.value().map(|a| {
+            (0..RANGE_TABLE_BITS)
+              .rev()
+              .fold(F::ZERO, |limb, j| limb.double() + le_bit(a, i * RANGE_TABLE_BITS + j))
+          });
+          let limb =
+            region.assign_advice(|| format!("limb {i}"), config.advice[0], offset, || limb)?;
+
+          let value = acc.value().zip(limb.value()).map(|(acc, limb)| *acc * shift + limb);
+          acc = region.assign_advice(|| "acc", config.advice[1], offset, || value)?;
+        }
+
+        region.constrain_equal(acc.cell(), a.0.cell())
+      },
+    )
+  }
+
+  // 1 iff a < b, for `a` and `b` that fit in `n_bits` bits: then a - b + 2^n_bits fits in
+  // n_bits + 1 bits, and its top bit is set iff a >= b
+  fn less_than(
+    &self,
+    mut layouter: impl Layouter<F>,
+    a: Self::Num,
+    b: Self::Num,
+    n_bits: usize,
+  ) -> Result<Self::Num, Error> {
+    // out of range, a - b can wrap around the modulus and flip the answer
+    self.range_check(layouter.namespace(|| "a fits"), a.clone(), n_bits)?;
+    self.range_check(layouter.namespace(|| "b fits"), b.clone(), n_bits)?;
+
+    let diff = self.sub(layouter.namespace(|| "a - b"), a, b)?;
+    let offset = (0..n_bits).fold(F::ONE, |offset, _| offset.double());
+    let shifted = self.add_constant(layouter.namespace(|| "a - b + 2^n"), diff, offset)?;
+
+    let bits = self.decompose(layouter.namespace(|| "decompose"), &shifted, n_bits + 1)?;
+    let not_lt = self.neg(layouter.namespace(|| "-(a >= b)"), bits[n_bits].clone())?;
+    self.add_constant(layouter.namespace(|| "1 - (a >= b)"), not_lt, F::ONE)
+  }
+
+  fn max(
+    &self,
+    mut layouter: impl Layouter<F>,
+    a: Self::Num,
+    b: Self::Num,
+  ) -> Result<Self::Num, Error> {
+    let lt =
+      self.less_than(layouter.namespace(|| "a < b"), a.clone(), b.clone(), COMPARISON_BITS)?;
+    self.select(layouter.namespace(|| "max"), lt, b, a)
+  }
+
+  fn min(
+    &self,
+    mut layouter: impl Layouter<F>,
+    a: Self::Num,
+    b: Self::Num,
+  ) -> Result<Self::Num, Error> {
+    let lt =
+      self.less_than(layouter.namespace(|| "a < b"), a.clone(), b.clone(), COMPARISON_BITS)?;
+    self.select(layouter.namespace(|| "min"), lt, a, b)
+  }
+
+  // a + 2^n_bits fits in n_bits + 1 bits, with the top bit set iff `a` is non-negative; that bit
+  // then gates `a` through
+  fn relu(
+    &self,
+    mut layouter: impl Layouter<F>,
+    a: Self::Num,
+    n_bits: usize,
+  ) -> Result<Self::Num, Error> {
+    let offset = (0..n_bits).fold(F::ONE, |offset, _| offset.double());
+    let shifted = self.add_constant(layouter.namespace(|| "a + 2^n"), a.clone(), offset)?;
+
+    let bits = self.decompose(layouter.namespace(|| "decompose"), &shifted, n_bits + 1)?;
+    self.mul(layouter.namespace(|| "sign * a"), bits[n_bits].clone(), a)
+  }
+
+  fn mul_chained(
+    &self,
+    mut layouter: impl Layouter<F>,
+    a: Self::Num,
+    factors: &[Self::Num],
+  ) -> Result<Self::Num, Error> {
+    let config = self.config();
+
+    layouter.assign_region(
+      || "mul chained",
+      |mut region: Region<'_, F>| {
+        // most `mul` layouts put a gate's output where the next gate wants its lhs; the same-row
+        // layout needs it copied there
+        let (_, [lhs, ..]) = config.mul_cells(0);
+        let mut acc = a.0.copy_advice(|| "lhs", &mut region, lhs.0, lhs.1)?;
+        for (i, factor) in factors.iter().enumerate() {
+          let (offset, [lhs, rhs, out]) = config.mul_cells(i);
+          if i > 0 && config.mul_cells(i - 1).1[2] != lhs {
+            acc = acc.copy_advice(|| format!("lhs {i}"), &mut region, lhs.0, lhs.1)?;
+          }
+          config.s_mul.enable(&mut region, offset)?;
+          factor.0.copy_advice(|| format!("factor {i}"), &mut region, rhs.0, rhs.1)?;
+
+          let value = acc.value().copied() * factor.0.value();
+          acc = region.assign_advice(|| "lhs * rhs", out.0, out.1, || value)?;
+        }
+        Ok(Number(acc))
+      },
+    )
+  }
+
+  fn fold_mul(
+    &self,
+    mut layouter: impl Layouter<F>,
+    init: Self::Num,
+    leaves: &[Self::Num],
+  ) -> Result<(Self::Num, Vec<Self::Num>), Error> {
+    let mut acc = init;
+    let mut intermediates = Vec::with_capacity(leaves.len());
+    for (i, leaf) in leaves.iter().enumerate() {
+      acc = self.mul(layouter.namespace(|| format!("acc * leaf {i}")), acc, leaf.clone())?;
+      intermediates.push(acc.clone());
+    }
+    Ok((acc, intermediates))
+  }
+
+  fn accumulate(
+    &self,
+    layouter: impl Layouter<F>,
+    prev_total: Self::Num,
+    addend: Self::Num,
+  ) -> Result<Self::Num, Error> {
+    self.add(layouter, prev_total, addend)
+  }
+
+  fn horner(
+    &self,
+    mut layouter: impl Layouter<F>,
+    coeffs: &[Self::Num],
+    x: Self::Num,
+  ) -> Result<Self::Num, Error> {
+    // from the leading coefficient down: acc = acc * x + coeffs[i]
+    let mut coeffs = coeffs.iter().enumerate().rev();
+    let mut acc = match coeffs.next() {
+      Some((_, leading)) => leading.clone(),
+      None => return self.zero(layouter.namespace(|| "horner of empty")),
+    };
+    for (i, coeff) in coeffs {
+      let mut layouter = layouter.namespace(|| format!("horner {i}"));
+      acc = match self.config().s_muladd {
+        Some(_) => self.mul_add(layouter, acc, x.clone(), coeff.clone())?,
+        // without a third advice column, fall back to a separate mul and add
+        None => {
+          let ax = self.mul(layouter.namespace(|| "acc * x"), acc, x.clone())?;
+          self.add(layouter.namespace(|| "acc * x + coeff"), ax, coeff.clone())?
+        },
+      };
+    }
+
+    Ok(acc)
+  }
+
+  fn assert_zero_if(
+    &self,
+    mut layouter: impl Layouter<F>,
+    cond: Self::Num,
+    val: Self::Num,
+  ) -> Result<(), Error> {
+    self.assert_bool(layouter.namespace(|| "cond is bool"), cond.clone())?;
+    let guarded = self.mul(layouter.namespace(|| "cond * val"), cond, val)?;
+    self.assert_equal_constant(layouter.namespace(|| "cond * val == 0"), guarded, F::ZERO)
+  }
+
+  // the inverse gate, a * a_inv == 1, holds exactly when a != 0; the inverse itself is unused
+  fn assert_nonzero(&self, layouter: impl Layouter<F>, a: Self::Num) -> Result<(), Error> {
+    self.inverse(layouter, a).map(|_| ())
+  }
+
+  fn expose_public(
+    &self,
+    mut layouter: impl Layouter<F>,
+    num: Self::Num,
+    row: usize,
+  ) -> Result<(), Error> {
+    let config = self.config();
+
+    layouter.constrain_instance(num.0.cell(), config.instance[0], row)
+  }
+
+  // expose to any of the configured instance columns, not just the first
+  fn expose_public_in(
+    &self,
+    mut layouter: impl Layouter<F>,
+    num: Self::Num,
+    column: usize,
+    row: usize,
+  ) -> Result<(), Error> {
+    let config = self.config();
+
+    // only configured columns have equality enabled
+    let column = *config.instance.get(column).ok_or(Error::Synthesis)?;
+
+    layouter.constrain_instance(num.0.cell(), column, row)
+  }
+
+  fn expose_public_vec(
+    &self,
+    mut layouter: impl Layouter<F>,
+    nums: &[Self::Num],
+    start_row: usize,
+    instance_rows: usize,
+  ) -> Result<(), Error> {
+    // check the whole range up front, rather than fail partway with some rows already exposed
+    match start_row.checked_add(nums.len()) {
+      Some(end) if end <= instance_rows => (),
+      _ => return Err(Error::InstanceTooLarge),
+    }
+
+    for (i, num) in nums.iter().enumerate() {
+      self.expose_public(
+        layouter.namespace(|| format!("expose {i}")),
+        num.clone(),
+        start_row + i,
+      )?;
+    }
+    Ok(())
+  }
+
+  fn square_and_expose(
+    &self,
+    mut layouter: impl Layouter<F>,
+    a: Self::Num,
+    row: usize,
+  ) -> Result<(), Error> {
+    let aa = self.square(layouter.namespace(|| "a * a"), a)?;
+    self.expose_public(layouter.namespace(|| "expose square"), aa, row)
+  }
+
+  fn compute_and_expose(
+    &self,
+    layouter: impl Layouter<F>,
+    num: Self::Num,
+    row: usize,
+  ) -> Result<(), Error> {
+    self.expose_public(layouter, num, row)
+  }
+}
+
+impl<F: PrimeField> ScalarMulChip<F> {
+  // decompose `a` into `n_bits` boolean cells, little-endian, constraining that they recompose to
+  // `a`
+  fn decompose(
+    &self,
+    mut layouter: impl Layouter<F>,
+    a: &Number<F>,
+    n_bits: usize,
+  ) -> Result<Vec<Number<F>>, Error> {
+    let config = self.config();
+
+    // leave room below the modulus, or the recomposition could wrap around
+    if n_bits >= F::NUM_BITS as usize {
+      return Err(Error::Synthesis);
+    }
+
+    layouter.assign_region(
+      || "decompose",
+      |mut region: Region<'_, F>| {
+        // the running sum starts from zero on the row above the first bit
+        let mut acc = region.assign_advice_from_constant(|| "acc", config.advice[1], 0, F::ZERO)?;
+
+        let mut bits = Vec::with_capacity(n_bits);
+        for i in (0..n_bits).rev() {
+          // most significant bit first, so each row doubles the sum so far
+          let offset = n_bits - i;
+          config.s_bits.enable(&mut region, offset)?;
+
+          let bit = a.0.value().map(|a| le_bit(a, i));
+          let bit =
+            region.assign_advice(|| format!("bit {i}"), config.advice[0], offset, || bit)?;
+
+          let value = acc.value().zip(bit.value()).map(|(acc, bit)| acc.double() + bit);
+          acc = region.assign_advice(|| "acc", config.advice[1], offset, || value)?;
+
+          bits.push(Number(bit));
+        }
+
+        // the full running sum must equal the input
+        region.constrain_equal(acc.cell(), a.0.cell())?;
+
+        bits.reverse();
+        Ok(bits)
+      },
+    )
+  }
+}
+
+// the `i`th bit of `a`, relying on the little-endian repr used by the pasta fields
+fn le_bit<F: PrimeField>(a: &F, i: usize) -> F {
+  let repr = a.to_repr();
+  F::from(u64::from((repr.as_ref()[i / 8] >> (i % 8)) & 1))
+}
+
+/// Where the `mul` gate puts its inputs `lhs` and `rhs` and its output
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MulLayout {
+  /// `rhs` beside `lhs`, and the output below `lhs`, in the next row
+  #[default]
+  TwoColNextRow,
+  /// `rhs` beside `lhs`, and the output beside `rhs`, so the gate spans a single row
+  ThreeColSameRow,
+  /// `lhs`, `rhs` and the output down a single column, so chained muls overlap: each output is the
+  /// next mul's `lhs`, at two rows per mul
+  Packed,
+}
+
+impl MulLayout {
+  /// the number of advice columns the `mul` gate spans
+  pub fn advice_columns(self) -> usize {
+    match self {
+      MulLayout::TwoColNextRow => 2,
+      MulLayout::ThreeColSameRow => 3,
+      MulLayout::Packed => 1,
+    }
+  }
+
+  // the (column, rotation from the row `s_mul` is enabled at) of the `mul` gate's lhs, rhs and
+  // output, shared by the gate and `ScalarMulConfig::mul_cells` so they agree on the cells
+  fn cells(self, a: &[Column<Advice>]) -> [(Column<Advice>, Rotation); 3] {
+    match self {
+      MulLayout::TwoColNextRow =>
+        [(a[0], Rotation::cur()), (a[1], Rotation::cur()), (a[0], Rotation::next())],
+      MulLayout::ThreeColSameRow =>
+        [(a[0], Rotation::cur()), (a[1], Rotation::cur()), (a[2], Rotation::cur())],
+      MulLayout::Packed =>
+        [(a[0], Rotation::prev()), (a[0], Rotation::cur()), (a[0], Rotation::next())],
+    }
+  }
+}
+
+#[derive(Clone, Debug)]
+pub struct ScalarMulConfig {
+  /// witness columns; at least two, with a third enabling the fan-in 3 gates
+  pub advice:      Vec<Column<Advice>>,
+  /// public inputs, one column per group of outputs; `expose_public` targets the first,
+  /// `expose_public_in` any of them by index
+  pub instance:    Vec<Column<Instance>>,
+  /// enabled for `load_constant`, and queried directly by the constant gates
+  pub constant:    Column<Fixed>,
+  /// `0..2^RANGE_TABLE_BITS`, filled by `load_range_table`; only `with_range_table`
+  pub range_table: Option<TableColumn>,
+  pub mul_layout:  MulLayout,
+  pub s_mul:       Selector,
+  pub s_add:       Selector,
+  pub s_sub:       Selector,
+  pub s_sq:        Selector,
+  pub s_neg:       Selector,
+  pub s_inv:       Selector,
+  pub s_div:       Selector,
+  pub s_cmul:      Selector,
+  pub s_addc:      Selector,
+  pub s_muladd:    Option<Selector>,
+  pub s_bool:      Selector,
+  pub s_sel:       Option<Selector>,
+  pub s_iz:        Selector,
+  pub s_bits:      Selector,
+  pub s_limbs:     Option<Selector>,
+}
+
+impl ScalarMulConfig {
+  pub fn configure<F: Field>(
+    meta: &mut ConstraintSystem<F>,
+    advice: &[Column<Advice>],
+    instance: &[Column<Instance>],
+    constant: Column<Fixed>,
+  ) -> Self {
+    Self::configure_with_layout(meta, advice, instance, constant, MulLayout::default())
+  }
+
+  /// `configure`, with the `mul` gate laid out per `mul_layout`
+  pub fn configure_with_layout<F: Field>(
+    meta: &mut ConstraintSystem<F>,
+    advice: &[Column<Advice>],
+    instance: &[Column<Instance>],
+    constant: Column<Fixed>,
+    mul_layout: MulLayout,
+  ) -> Self {
+    // most gates are fan-in 2; the fan-in 3 gates are only registered given a third column
+    assert!(advice.len() >= 2, "ScalarMulConfig needs at least two advice columns");
+    assert!(!instance.is_empty(), "ScalarMulConfig needs at least one instance column");
+    let three_input = advice.len() >= 3;
+    assert!(
+      three_input || mul_layout != MulLayout::ThreeColSameRow,
+      "MulLayout::ThreeColSameRow needs three advice columns"
+    );
+
+    // specify the columns that can be compared used by the constraint system
+    for column in instance {
+      meta.enable_equality(*column);
+    }
+    meta.enable_constant(constant);
+    for column in advice {
+      meta.enable_equality(*column);
+    }
+
+    // meta selector is used to enable gates
+    let s_add = meta.selector();
+    let s_sub = meta.selector();
+    let s_sq = meta.selector();
+    let s_neg = meta.selector();
+    let s_inv = meta.selector();
+    let s_div = meta.selector();
+    let s_cmul = meta.selector();
+    let s_addc = meta.selector();
+    let s_muladd = three_input.then(|| meta.selector());
+    let s_bool = meta.selector();
+    let s_sel = three_input.then(|| meta.selector());
+    let s_iz = meta.selector();
+    let s_bits = meta.selector();
+
+    // Define our arithmetic gate. `add` and `sub`, and `mul` under the default layout, share one
+    // layout over the same two advice columns, so rather than a gate each, register them as a
+    // single gate with a selector per operation. An instruction enables exactly one of the
+    // selectors on its row, which turns off the other constraints.
+    let s_mul = meta.selector();
+    let shares_arith = mul_layout == MulLayout::TwoColNextRow;
+    meta.create_gate("arith", |meta| {
+      // To implement the gate, we need three advice cells and the selector
+      // cells. We arrange them like so:
+      //
+      // | a0  | a1  | s_mul | s_add | s_sub |
+      // |-----|-----|-------|-------|-------|
+      // | lhs | rhs | s_mul | s_add | s_sub |
+      // | out |     |       |       |       |
+      //
+      // Gates may refer to any relative offsets we want, but each distinct
+      // offset adds a cost to the proof. The most common offsets are 0 (the
+      // current row), 1 (the next row), and -1 (the previous row), for which
+      // `Rotation` has specific constructors.
+      let lhs = meta.query_advice(advice[0], Rotation::cur());
+      let rhs = meta.query_advice(advice[1], Rotation::cur());
+      let out = meta.query_advice(advice[0], Rotation::next());
+      let s_add = meta.query_selector(s_add);
+      let s_sub = meta.query_selector(s_sub);
+
+      // Finally, we return the polynomial expressions that constrain this gate.
+      //
+      // The polynomial expressions returned from `create_gate` will be
+      // constrained by the proving system to equal zero. Our expressions
+      // have the following properties:
+      // - When every selector is 0, any value is allowed in lhs, rhs, and out.
+      // - When s_add != 0, this constrains lhs + rhs = out, and likewise for s_sub and s_mul.
+      let mut constraints = vec![
+        s_add * (lhs.clone() + rhs.clone() - out.clone()),
+        s_sub * (lhs.clone() - rhs.clone() - out.clone()),
+      ];
+      if shares_arith {
+        constraints.push(meta.query_selector(s_mul) * (lhs * rhs - out));
+      }
+      constraints
+    });
+    // any other layout moves the mul's cells, so it gets a gate querying only those
+    if !shares_arith {
+      Self::mul_gate(meta, advice, mul_layout, s_mul);
+    }
+
+    // Define our squaring gate, which only needs the single input cell
+    meta.create_gate("square", |meta| {
+      // | a0  | s_sq |
+      // |-----|------|
+      // | lhs | s_sq |
+      // | out |      |
+      let lhs = meta.query_advice(advice[0], Rotation::cur());
+      let out = meta.query_advice(advice[0], Rotation::next());
+      let s_sq = meta.query_selector(s_sq);
+
+      vec![s_sq * (lhs.clone() * lhs - out)]
+    });
+
+    // Define our negation gate, constraining a single row
+    meta.create_gate("neg", |meta| {
+      // | a0  | a1  | s_neg |
+      // |-----|-----|-------|
+      // | lhs | out | s_neg |
+      let lhs = meta.query_advice(advice[0], Rotation::cur());
+      let out = meta.query_advice(advice[1], Rotation::cur());
+      let s_neg = meta.query_selector(s_neg);
+
+      vec![s_neg * (lhs + out)]
+    });
+
+    // Define our inversion gate, same single-row layout as `neg`
+    meta.create_gate("inverse", |meta| {
+      // | a0  | a1     | s_inv |
+      // |-----|--------|-------|
+      // | lhs | lhs^-1 | s_inv |
+      let lhs = meta.query_advice(advice[0], Rotation::cur());
+      let out = meta.query_advice(advice[1], Rotation::cur());
+      let s_inv = meta.query_selector(s_inv);
+
+      vec![s_inv * (lhs * out - Expression::Constant(F::ONE))]
+    });
+
+    // Define our division gate, same layout as `mul` but constraining out * rhs = lhs
+    meta.create_gate("div", |meta| {
+      let lhs = meta.query_advice(advice[0], Rotation::cur());
+      let rhs = meta.query_advice(advice[1], Rotation::cur());
+      let out = meta.query_advice(advice[0], Rotation::next());
+      let s_div = meta.query_selector(s_div);
+
+      vec![s_div * (out * rhs - lhs)]
+    });
+
+    // Define our multiply-by-constant gate, reading the constant from the fixed column
+    meta.create_gate("mul by constant", |meta| {
+      // | a0  | constant | s_cmul |
+      // |-----|----------|--------|
+      // | lhs | c        | s_cmul |
+      // | out |          |        |
+      let lhs = meta.query_advice(advice[0], Rotation::cur());
+      let c = meta.query_fixed(constant, Rotation::cur());
+      let out = meta.query_advice(advice[0], Rotation::next());
+      let s_cmul = meta.query_selector(s_cmul);
+
+      vec![s_cmul * (lhs * c - out)]
+    });
+
+    // Define our add-constant gate, same layout as the multiply-by-constant gate
+    meta.create_gate("add constant", |meta| {
+      let lhs = meta.query_advice(advice[0], Rotation::cur());
+      let c = meta.query_fixed(constant, Rotation::cur());
+      let out = meta.query_advice(advice[0], Rotation::next());
+      let s_addc = meta.query_selector(s_addc);
+
+      vec![s_addc * (lhs + c - out)]
+    });
+
+    // Define our fused multiply-add gate, using all three advice columns
+    if let Some(s_muladd) = s_muladd {
+      meta.create_gate("mul add", |meta| {
+        // | a0  | a1  | a2  | s_muladd |
+        // |-----|-----|-----|----------|
+        // | a   | b   | c   | s_muladd |
+        // | out |     |     |          |
+        let a = meta.query_advice(advice[0], Rotation::cur());
+        let b = meta.query_advice(advice[1], Rotation::cur());
+        let c = meta.query_advice(advice[2], Rotation::cur());
+        let out = meta.query_advice(advice[0], Rotation::next());
+        let s_muladd = meta.query_selector(s_muladd);
+
+        vec![s_muladd * (a * b + c - out)]
+      });
+    }
+
+    // Define our boolean gate, which is only satisfied by 0 and 1
+    meta.create_gate("bool", |meta| {
+      let a = meta.query_advice(advice[0], Rotation::cur());
+      let s_bool = meta.query_selector(s_bool);
+
+      vec![s_bool * a.clone() * (Expression::Constant(F::ONE) - a)]
+    });
+
+    // Define our selection gate, out = cond * (a - b) + b, also using all three advice columns
+    if let Some(s_sel) = s_sel {
+      meta.create_gate("select", |meta| {
+        // | a0   | a1  | a2  | s_sel |
+        // |------|-----|-----|-------|
+        // | cond | a   | b   | s_sel |
+        // | out  |     |     |       |
+        let cond = meta.query_advice(advice[0], Rotation::cur());
+        let a = meta.query_advice(advice[1], Rotation::cur());
+        let b = meta.query_advice(advice[2], Rotation::cur());
+        let out = meta.query_advice(advice[0], Rotation::next());
+        let s_sel = meta.query_selector(s_sel);
+
+        vec![s_sel * (cond * (a - b.clone()) + b - out)]
+      });
+    }
+
+    // Define our is-zero gate, the standard witnessed-inverse construction
+    meta.create_gate("is zero", |meta| {
+      // | a0  | a1    | s_iz |
+      // |-----|-------|------|
+      // | a   | a_inv | s_iz |
+      // | out |       |      |
+      let a = meta.query_advice(advice[0], Rotation::cur());
+      let a_inv = meta.query_advice(advice[1], Rotation::cur());
+      let out = meta.query_advice(advice[0], Rotation::next());
+      let s_iz = meta.query_selector(s_iz);
+
+      // - out = 1 - a * a_inv forces out = 1 when a = 0
+      // - a * out = 0 forces out = 0 when a != 0
+      vec![
+        s_iz.clone() * a.clone() * out.clone(),
+        s_iz * (out - (Expression::Constant(F::ONE) - a * a_inv)),
+      ]
+    });
+
+    // Define our bit decomposition gate, accumulating bits most significant first
+    meta.create_gate("bits", |meta| {
+      // | a0  | a1       | s_bits |
+      // |-----|----------|--------|
+      // |     | acc_prev |        |
+      // | bit | acc      | s_bits |
+      let bit = meta.query_advice(advice[0], Rotation::cur());
+      let acc_prev = meta.query_advice(advice[1], Rotation::prev());
+      let acc = meta.query_advice(advice[1], Rotation::cur());
+      let s_bits = meta.query_selector(s_bits);
+
+      vec![
+        s_bits.clone() * bit.clone() * (Expression::Constant(F::ONE) - bit.clone()),
+        s_bits * (acc - (acc_prev * Expression::Constant(F::ONE.double()) + bit)),
+      ]
+    });
+
+    ScalarMulConfig {
+      advice: advice.to_vec(),
+      instance: instance.to_vec(),
+      constant,
+      range_table: None,
+      mul_layout,
+      s_mul,
+      s_add,
+      s_sub,
+      s_sq,
+      s_neg,
+      s_inv,
+      s_div,
+      s_cmul,
+      s_addc,
+      s_muladd,
+      s_bool,
+      s_sel,
+      s_iz,
+      s_bits,
+      s_limbs: None,
+    }
+  }
+
+  // Define a multiplication gate apart from the arithmetic gate, for the layouts that move its
+  // cells: `ThreeColSameRow` puts out in a2, beside rhs, and `Packed` puts lhs, rhs and out in a0,
+  // one per row from the previous one. The gate queries only the three cells its layout assigns.
+  fn mul_gate<F: Field>(
+    meta: &mut ConstraintSystem<F>,
+    advice: &[Column<Advice>],
+    mul_layout: MulLayout,
+    s_mul: Selector,
+  ) {
+    meta.create_gate("mul", |meta| {
+      let [lhs, rhs, out] =
+        mul_layout.cells(advice).map(|(column, at)| meta.query_advice(column, at));
+      let s_mul = meta.query_selector(s_mul);
+
+      // - When s_mul = 0, any value is allowed in lhs, rhs, and out.
+      // - When s_mul != 0, this constrains lhs * rhs = out.
+      vec![s_mul * (lhs * rhs - out)]
+    });
+  }
+
+  // for the `i`th of a run of `mul` gates in one region, the offset to enable `s_mul` at, and the
+  // (column, offset) of its lhs, rhs and output
+  fn mul_cells(&self, i: usize) -> (usize, [(Column<Advice>, usize); 3]) {
+    // `Packed` muls overlap by a row, the output of one being the lhs of the next
+    let row = match self.mul_layout {
+      MulLayout::TwoColNextRow | MulLayout::ThreeColSameRow => i,
+      MulLayout::Packed => 2 * i + 1,
+    };
+    let cells = self.mul_layout.cells(&self.advice);
+    (row, cells.map(|(column, at)| (column, (row as i32 + at.0) as usize)))
+  }
+
+  /// Register the range table and its lookup, for `range_check_lookup`. Opt-in, since a lookup
+  /// raises the constraint system's degree from 3 to 5, and so the cost of every proof
+  pub fn with_range_table<F: Field>(mut self, meta: &mut ConstraintSystem<F>) -> Self {
+    let advice = &self.advice;
+    let s_limbs = meta.complex_selector();
+    let range_table = meta.lookup_table_column();
+
+    // Define our limbs gate, the running sum of `decompose` over whole table-sized limbs, with each
+    // limb looked up in the range table rather than constrained bit by bit
+    meta.create_gate("limbs", |meta| {
+      // | a0   | a1       | s_limbs |
+      // |------|----------|---------|
+      // |      | acc_prev |         |
+      // | limb | acc      | s_limbs |
+      let limb = meta.query_advice(advice[0], Rotation::cur());
+      let acc = meta.query_advice(advice[1], Rotation::cur());
+      let acc_prev = meta.query_advice(advice[1], Rotation::prev());
+      let s_limbs = meta.query_selector(s_limbs);
+
+      let shift = (0..RANGE_TABLE_BITS).fold(F::ONE, |shift, _| shift.double());
+      let shift = Expression::Constant(shift);
+      vec![s_limbs * (acc - (acc_prev * shift + limb))]
+    });
+
+    // with s_limbs off the input is 0, which is in the table
+    meta.lookup(|meta| {
+      let limb = meta.query_advice(advice[0], Rotation::cur());
+      let s_limbs = meta.query_selector(s_limbs);
+
+      vec![(s_limbs * limb, range_table)]
+    });
+
+    self.range_table = Some(range_table);
+    self.s_limbs = Some(s_limbs);
+    self
+  }
+}
 
 mod chip {
   use std::marker::PhantomData;
@@ -200,3 +1791,610 @@ mod chip {
     pub fn new(config: <Self as Chip<F>>::Config) -> Self { Self { config, _marker: PhantomData } }
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use halo2_proofs::{
+    circuit::{Layouter, SimpleFloorPlanner, Value},
+    dev::MockProver,
+    pasta::{group::ff::Field, vesta, Fp},
+    plonk::{Circuit, ConstraintSystem, Error},
+    poly::commitment::Params,
+  };
+
+  use super::{ScalarMulChip, ScalarMulConfig, ScalarMulInstructions};
+  use crate::{
+    cost::{copy_count, describe_cost},
+    prover::keygen,
+    reference,
+    testing::{assert_fails, assert_satisfied, assert_synthesis_fails, Op, OpCircuit},
+  };
+
+  // room for the 256-row range table
+  const K: u32 = 9;
+
+  // assert that `op` on `inputs` exposes exactly `outputs`
+  fn assert_outputs(op: Op, inputs: &[u64], outputs: &[Fp]) {
+    let inputs: Vec<_> = inputs.iter().copied().map(Fp::from).collect();
+    assert_satisfied(K, &OpCircuit::new(op, &inputs), vec![outputs.to_vec()]);
+  }
+
+  // assert that `op` on `inputs` violates a constraint when claimed to expose `outputs`
+  fn assert_rejects(op: Op, inputs: &[u64], outputs: &[Fp]) {
+    let inputs: Vec<_> = inputs.iter().copied().map(Fp::from).collect();
+    assert_fails(K, &OpCircuit::new(op, &inputs), vec![outputs.to_vec()]);
+  }
+
+  // assert that `op` refuses `inputs` during synthesis
+  fn assert_refuses(op: Op, inputs: &[u64]) {
+    let inputs: Vec<_> = inputs.iter().copied().map(Fp::from).collect();
+    assert_synthesis_fails(K, &OpCircuit::new(op, &inputs), vec![vec![]]);
+  }
+
+  fn fp<const N: usize>(xs: [u64; N]) -> [Fp; N] { xs.map(Fp::from) }
+
+  // every op, with the number of inputs it takes
+  fn every_op() -> Vec<(Op, usize)> {
+    vec![
+      (Op::Load, 1),
+      (Op::LoadPrivateVec, 3),
+      (Op::AssignBulk(1), 3),
+      (Op::LoadPrivateWithValue, 1),
+      (Op::LoadConstant(Fp::from(7)), 0),
+      (Op::Zero, 0),
+      (Op::One, 0),
+      (Op::LoadInstance(1), 0),
+      (Op::MulByPublicConstant(1), 1),
+      (Op::Mul, 2),
+      (Op::MulSelf, 1),
+      (Op::MulInline, 2),
+      (Op::Add, 2),
+      (Op::Sub, 2),
+      (Op::Square, 1),
+      (Op::Sqrt, 1),
+      (Op::Neg, 1),
+      (Op::Inverse, 1),
+      (Op::Div, 2),
+      (Op::MulByConstant(Fp::from(3)), 1),
+      (Op::AddConstant(Fp::from(10)), 1),
+      (Op::MulAdd, 3),
+      (Op::MulMany, 6),
+      (Op::Dot, 6),
+      (Op::Sum, 4),
+      (Op::Product, 3),
+      (Op::AssertBool, 1),
+      (Op::Select, 3),
+      (Op::IsZero, 1),
+      (Op::IsEqual, 2),
+      (Op::AssertEqual, 2),
+      (Op::AssertEqualConstant(Fp::from(5)), 1),
+      (Op::Pow(10), 1),
+      (Op::RangeCheck(3), 1),
+      (Op::RangeCheckLookup(16), 1),
+      (Op::ToBits(4), 1),
+      (Op::ScalarMulBits(8), 2),
+      (Op::LessThan(4), 2),
+      (Op::Max, 2),
+      (Op::Min, 2),
+      (Op::Relu(8), 1),
+      (Op::MulChained, 3),
+      (Op::FoldMul, 4),
+      (Op::Horner, 4),
+      (Op::AssertZeroIf, 2),
+      (Op::AssertNonzero, 1),
+      (Op::SquareAndExpose, 1),
+      (Op::ComputeAndExpose, 2),
+      (Op::ExposeNext, 3),
+      (Op::ExposePublicVec(1, 4), 3),
+    ]
+  }
+
+  // `MockProver` refuses to assign unknown witnesses, so keygen, which synthesizes without them, is
+  // what exercises the unknown path; zero inputs would trip the known-input checks of `inverse` and
+  // `div`, so this also shows those checks let unknown values through
+  #[test]
+  fn unknown_witnesses() {
+    let params = Params::<vesta::Affine>::new(K);
+    for (op, n_inputs) in every_op() {
+      let circuit = OpCircuit::new(op, &vec![Fp::ZERO; n_inputs]).without_witnesses();
+      if let Err(e) = keygen(&params, &circuit) {
+        panic!("{op:?} failed on unknown witnesses: {e:?}");
+      }
+    }
+  }
+
+  #[test]
+  fn mul() {
+    let (a, b) = (Fp::from(u64::MAX), Fp::from(3));
+    assert_outputs(Op::Mul, &[u64::MAX, 3], &[reference::mul(a, b)]);
+    assert_outputs(Op::MulInline, &[u64::MAX, 3], &[reference::mul(a, b)]);
+    assert_rejects(Op::Mul, &[u64::MAX, 3], &[reference::add(a, b)]);
+    assert_outputs(Op::MulSelf, &[3], &[reference::mul(Fp::from(3), Fp::from(3))]);
+  }
+
+  #[test]
+  fn add() {
+    let (a, b) = (Fp::from(u64::MAX), Fp::from(3));
+    assert_outputs(Op::Add, &[u64::MAX, 3], &[reference::add(a, b)]);
+    assert_rejects(Op::Add, &[u64::MAX, 3], &[reference::mul(a, b)]);
+  }
+
+  #[test]
+  fn mul_add() {
+    let [a, b, c] = fp([2, 3, 4]);
+    assert_outputs(Op::MulAdd, &[2, 3, 4], &[reference::add(reference::mul(a, b), c)]);
+    assert_rejects(Op::MulAdd, &[2, 3, 4], &[reference::mul(reference::mul(a, b), c)]);
+  }
+
+  #[test]
+  fn sub() {
+    assert_outputs(Op::Sub, &[5, 3], &fp([2]));
+    assert_outputs(Op::Sub, &[3, 5], &[-Fp::from(2)]);
+    assert_rejects(Op::Sub, &[5, 3], &fp([8]));
+  }
+
+  #[test]
+  fn square() {
+    assert_outputs(Op::Square, &[3], &[reference::pow(Fp::from(3), 2)]);
+    assert_rejects(Op::Square, &[3], &fp([6]));
+  }
+
+  #[test]
+  fn neg() {
+    assert_outputs(Op::Neg, &[0], &fp([0]));
+    assert_outputs(Op::Neg, &[5], &[-Fp::from(5)]);
+    assert_rejects(Op::Neg, &[5], &fp([5]));
+  }
+
+  #[test]
+  fn inverse() {
+    assert_outputs(Op::Inverse, &[4], &[Fp::from(4).invert().unwrap()]);
+    assert_rejects(Op::Inverse, &[4], &fp([4]));
+    assert_refuses(Op::Inverse, &[0]);
+  }
+
+  #[test]
+  fn div() {
+    assert_outputs(Op::Div, &[6, 3], &fp([2]));
+    assert_rejects(Op::Div, &[6, 3], &fp([18]));
+    assert_refuses(Op::Div, &[6, 0]);
+  }
+
+  #[test]
+  fn mul_by_constant() {
+    assert_outputs(Op::MulByConstant(Fp::from(3)), &[7], &fp([21]));
+    assert_rejects(Op::MulByConstant(Fp::from(3)), &[7], &fp([10]));
+  }
+
+  #[test]
+  fn add_constant() {
+    assert_outputs(Op::AddConstant(Fp::from(10)), &[5], &fp([15]));
+    assert_rejects(Op::AddConstant(Fp::from(10)), &[5], &fp([50]));
+  }
+
+  #[test]
+  fn mul_many() {
+    let (a, b) = (fp([2, 3, 4]), fp([5, 6, 7]));
+    let products: Vec<_> = a.iter().zip(&b).map(|(a, b)| reference::mul(*a, *b)).collect();
+    assert_outputs(Op::MulMany, &[2, 3, 4, 5, 6, 7], &products);
+    assert_rejects(Op::MulMany, &[2, 3, 4, 5, 6, 7], &fp([10, 18, 29]));
+    // an odd count splits into slices of different lengths
+    assert_refuses(Op::MulMany, &[2, 3, 4, 5, 6]);
+  }
+
+  #[test]
+  fn dot() {
+    let (a, b) = (fp([1, 2, 3]), fp([4, 5, 6]));
+    assert_outputs(Op::Dot, &[1, 2, 3, 4, 5, 6], &[reference::dot(&a, &b)]);
+    assert_outputs(Op::Dot, &[], &[reference::dot::<Fp>(&[], &[])]);
+    assert_rejects(Op::Dot, &[1, 2, 3, 4, 5, 6], &fp([31]));
+    assert_refuses(Op::Dot, &[1, 2, 3, 4, 5]);
+  }
+
+  #[test]
+  fn sum() {
+    let total = fp([1, 2, 3, 4]).into_iter().fold(Fp::ZERO, reference::add);
+    assert_outputs(Op::Sum, &[1, 2, 3, 4], &[total]);
+    assert_outputs(Op::Sum, &[], &fp([0]));
+    assert_rejects(Op::Sum, &[1, 2, 3, 4], &fp([9]));
+  }
+
+  #[test]
+  fn product() {
+    let product = fp([2, 3, 4]).into_iter().fold(Fp::ONE, reference::mul);
+    assert_outputs(Op::Product, &[2, 3, 4], &[product]);
+    assert_outputs(Op::Product, &[], &fp([1]));
+    assert_rejects(Op::Product, &[2, 3, 4], &fp([9]));
+  }
+
+  #[test]
+  fn assert_bool() {
+    assert_outputs(Op::AssertBool, &[0], &[]);
+    assert_outputs(Op::AssertBool, &[1], &[]);
+    assert_rejects(Op::AssertBool, &[2], &[]);
+  }
+
+  #[test]
+  fn select() {
+    assert_outputs(Op::Select, &[1, 7, 9], &fp([7]));
+    assert_outputs(Op::Select, &[0, 7, 9], &fp([9]));
+    assert_rejects(Op::Select, &[1, 7, 9], &fp([9]));
+    // 2 * (7 - 9) + 9 satisfies the select gate, but not the boolean check on cond
+    assert_rejects(Op::Select, &[2, 7, 9], &fp([5]));
+  }
+
+  #[test]
+  fn is_zero() {
+    assert_outputs(Op::IsZero, &[0], &fp([1]));
+    assert_outputs(Op::IsZero, &[5], &fp([0]));
+    assert_rejects(Op::IsZero, &[5], &fp([1]));
+  }
+
+  #[test]
+  fn is_equal() {
+    assert_outputs(Op::IsEqual, &[4, 4], &fp([1]));
+    assert_outputs(Op::IsEqual, &[4, 5], &fp([0]));
+    assert_rejects(Op::IsEqual, &[4, 5], &fp([1]));
+  }
+
+  #[test]
+  fn assert_equal() {
+    assert_outputs(Op::AssertEqual, &[4, 4], &[]);
+    assert_rejects(Op::AssertEqual, &[4, 5], &[]);
+  }
+
+  #[test]
+  fn pow() {
+    assert_outputs(Op::Pow(10), &[2], &[reference::pow(Fp::from(2), 10)]);
+    assert_outputs(Op::Pow(0), &[7], &[reference::pow(Fp::from(7), 0)]);
+    assert_outputs(Op::Pow(u64::MAX), &[7], &[reference::pow(Fp::from(7), u64::MAX)]);
+    assert_rejects(Op::Pow(10), &[2], &fp([1000]));
+  }
+
+  #[test]
+  fn range_check() {
+    assert_outputs(Op::RangeCheck(3), &[5], &[]);
+    assert_rejects(Op::RangeCheck(3), &[8], &[]);
+  }
+
+  #[test]
+  fn range_check_lookup() {
+    assert_outputs(Op::RangeCheckLookup(16), &[0], &[]);
+    assert_outputs(Op::RangeCheckLookup(16), &[65535], &[]);
+    assert_rejects(Op::RangeCheckLookup(16), &[65536], &[]);
+    // limbs are whole bytes
+    assert_refuses(Op::RangeCheckLookup(12), &[5]);
+  }
+
+  #[test]
+  fn to_bits() {
+    assert_outputs(Op::ToBits(4), &[13], &fp([1, 0, 1, 1]));
+    // 16 needs a fifth bit, so the four it gets don't recompose to it
+    assert_rejects(Op::ToBits(4), &[16], &fp([0, 0, 0, 0]));
+  }
+
+  #[test]
+  fn scalar_mul_bits() {
+    assert_outputs(Op::ScalarMulBits(8), &[3, 200], &fp([600]));
+    assert_rejects(Op::ScalarMulBits(8), &[3, 200], &fp([603]));
+  }
+
+  #[test]
+  fn loaded_values_read_back() {
+    assert_outputs(Op::Load, &[42], &fp([42]));
+    assert_outputs(Op::LoadPrivateWithValue, &[42], &fp([42]));
+    assert_outputs(Op::LoadPrivateVec, &[1, 2, 3], &fp([1, 2, 3]));
+    assert_rejects(Op::LoadPrivateVec, &[1, 2, 3], &fp([1, 3, 2]));
+  }
+
+  #[test]
+  fn assign_bulk() {
+    let values: Vec<u64> = (0..100).collect();
+    let outputs: Vec<Fp> = values.iter().copied().map(Fp::from).collect();
+    assert_outputs(Op::AssignBulk(1), &values, &outputs);
+
+    let mut swapped = outputs.clone();
+    swapped.swap(17, 83);
+    assert_rejects(Op::AssignBulk(1), &values, &swapped);
+    // only three advice columns
+    assert_refuses(Op::AssignBulk(3), &values);
+  }
+
+  #[test]
+  fn constants() {
+    assert_outputs(Op::LoadConstant(Fp::from(7)), &[], &fp([7]));
+    assert_outputs(Op::Zero, &[], &fp([0]));
+    assert_outputs(Op::One, &[], &fp([1]));
+    assert_rejects(Op::One, &[], &fp([0]));
+  }
+
+  #[test]
+  fn load_instance() {
+    // loaded from row 1, exposed again at row 0
+    assert_outputs(Op::LoadInstance(1), &[], &fp([9, 9]));
+    assert_rejects(Op::LoadInstance(1), &[], &fp([9, 8]));
+  }
+
+  #[test]
+  fn load_public_constant() {
+    assert_outputs(Op::MulByPublicConstant(1), &[7], &fp([21, 3]));
+    assert_rejects(Op::MulByPublicConstant(1), &[7], &fp([21, 4]));
+  }
+
+  #[test]
+  fn expose_helpers() {
+    assert_outputs(Op::SquareAndExpose, &[3], &fp([9]));
+    assert_rejects(Op::SquareAndExpose, &[3], &fp([6]));
+    assert_outputs(Op::ComputeAndExpose, &[2, 3], &fp([6]));
+    assert_rejects(Op::ComputeAndExpose, &[2, 3], &fp([5]));
+  }
+
+  #[test]
+  fn instance_writer() {
+    assert_outputs(Op::ExposeNext, &[4, 5, 6], &fp([4, 5, 6]));
+    assert_rejects(Op::ExposeNext, &[4, 5, 6], &fp([4, 6, 5]));
+  }
+
+  #[test]
+  fn less_than() {
+    assert_outputs(Op::LessThan(4), &[2, 5], &fp([1]));
+    assert_outputs(Op::LessThan(4), &[5, 2], &fp([0]));
+    assert_outputs(Op::LessThan(4), &[5, 5], &fp([0]));
+    assert_rejects(Op::LessThan(4), &[5, 2], &fp([1]));
+  }
+
+  #[test]
+  fn less_than_range_checks_its_inputs() {
+    // 0 - (p - 1) + 2^4 = 17 has its top bit set, so without the range checks, 0 < p - 1 would
+    // come out false
+    let wraps = OpCircuit::new(Op::LessThan(4), &[Fp::ZERO, -Fp::ONE]);
+    assert_fails(K, &wraps, vec![vec![Fp::ZERO]]);
+    assert_rejects(Op::LessThan(4), &[16, 2], &fp([0]));
+  }
+
+  #[test]
+  fn max_min() {
+    assert_outputs(Op::Max, &[3, 5], &fp([5]));
+    assert_outputs(Op::Min, &[3, 5], &fp([3]));
+    assert_outputs(Op::Max, &[5, 3], &fp([5]));
+    assert_outputs(Op::Min, &[5, 3], &fp([3]));
+    assert_rejects(Op::Max, &[3, 5], &fp([3]));
+
+    // p - 1 doesn't fit in `COMPARISON_BITS`
+    let wide = OpCircuit::new(Op::Max, &[Fp::ZERO, -Fp::ONE]);
+    assert_fails(K, &wide, vec![vec![Fp::ZERO]]);
+  }
+
+  #[test]
+  fn relu() {
+    assert_outputs(Op::Relu(8), &[5], &fp([5]));
+    assert_rejects(Op::Relu(8), &[5], &fp([0]));
+
+    // -5, as the high field element p - 5
+    let negative = OpCircuit::new(Op::Relu(8), &[-Fp::from(5)]);
+    assert_satisfied(K, &negative, vec![vec![Fp::ZERO]]);
+    assert_fails(K, &negative, vec![vec![-Fp::from(5)]]);
+  }
+
+  #[test]
+  fn fold_mul() {
+    // the final accumulator, then each intermediate one
+    assert_outputs(Op::FoldMul, &[1, 2, 3, 4], &fp([24, 2, 6, 24]));
+    assert_rejects(Op::FoldMul, &[1, 2, 3, 4], &fp([24, 2, 5, 24]));
+  }
+
+  #[test]
+  fn assert_equal_constant() {
+    assert_outputs(Op::AssertEqualConstant(Fp::from(5)), &[5], &[]);
+    assert_rejects(Op::AssertEqualConstant(Fp::from(5)), &[6], &[]);
+  }
+
+  #[test]
+  fn horner() {
+    // 1 + 2 * 2 + 3 * 2^2
+    assert_outputs(Op::Horner, &[1, 2, 3, 2], &fp([17]));
+    assert_outputs(Op::Horner, &[2], &fp([0]));
+    assert_rejects(Op::Horner, &[1, 2, 3, 2], &fp([11]));
+  }
+
+  #[test]
+  fn assert_zero_if() {
+    assert_outputs(Op::AssertZeroIf, &[1, 0], &[]);
+    assert_outputs(Op::AssertZeroIf, &[0, 5], &[]);
+    assert_rejects(Op::AssertZeroIf, &[1, 5], &[]);
+    // cond * val is zero, but cond isn't boolean
+    assert_rejects(Op::AssertZeroIf, &[2, 0], &[]);
+  }
+
+  #[test]
+  fn assert_nonzero() {
+    assert_outputs(Op::AssertNonzero, &[5], &[]);
+    assert_refuses(Op::AssertNonzero, &[0]);
+  }
+
+  #[test]
+  fn sqrt() {
+    let root = Fp::from(9).sqrt().unwrap();
+    assert_eq!(root.square(), Fp::from(9));
+    assert_outputs(Op::Sqrt, &[9], &[root]);
+    assert_rejects(Op::Sqrt, &[9], &fp([9]));
+
+    // 5, the field's multiplicative generator, is a non-residue
+    assert!(bool::from(Fp::from(5).sqrt().is_none()));
+    assert_refuses(Op::Sqrt, &[5]);
+  }
+
+  #[test]
+  fn expose_public_vec() {
+    assert_outputs(Op::ExposePublicVec(0, 3), &[4, 5, 6], &fp([4, 5, 6]));
+    // row 0 is left free
+    assert_outputs(Op::ExposePublicVec(1, 4), &[4, 5, 6], &fp([9, 4, 5, 6]));
+    assert_rejects(Op::ExposePublicVec(0, 3), &[4, 5, 6], &fp([4, 5, 7]));
+
+    // one row past the end of the column
+    let circuit = OpCircuit::new(Op::ExposePublicVec(1, 3), &fp([4, 5, 6]));
+    let result = MockProver::run(K, &circuit, vec![fp([9, 4, 5]).to_vec()]);
+    assert!(matches!(result, Err(Error::InstanceTooLarge)), "expected InstanceTooLarge");
+  }
+
+  /// (a + b) * c: an `add` feeding a `mul`, both selected within the shared arithmetic gate
+  #[derive(Default)]
+  struct AddThenMulCircuit {
+    inputs: [Value<Fp>; 3],
+  }
+
+  impl Circuit<Fp> for AddThenMulCircuit {
+    type Config = ScalarMulConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self { Self::default() }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+      let advice = [meta.advice_column(), meta.advice_column(), meta.advice_column()];
+      let instance = meta.instance_column();
+      let constant = meta.fixed_column();
+      Self::Config::configure(meta, &advice, &[instance], constant)
+    }
+
+    fn synthesize(
+      &self,
+      config: Self::Config,
+      mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+      let chip = ScalarMulChip::<Fp>::new(config);
+      let [a, b, c] = self.inputs;
+      let a = chip.load_private(layouter.namespace(|| "load a"), a)?;
+      let b = chip.load_private(layouter.namespace(|| "load b"), b)?;
+      let c = chip.load_private(layouter.namespace(|| "load c"), c)?;
+
+      let sum = chip.add(layouter.namespace(|| "a + b"), a, b)?;
+      let product = chip.mul(layouter.namespace(|| "(a + b) * c"), sum, c)?;
+      chip.expose_public(layouter.namespace(|| "expose (a + b) * c"), product, 0)
+    }
+  }
+
+  #[test]
+  fn add_then_mul() {
+    let circuit = AddThenMulCircuit { inputs: fp([2, 3, 4]).map(Value::known) };
+    assert_satisfied(K, &circuit, vec![vec![Fp::from(20)]]);
+    // 2 * 3 + 4, as if the mul had come first
+    assert_fails(K, &circuit, vec![vec![Fp::from(10)]]);
+  }
+
+  // pins the constraint system `configure` builds over three advice columns, so a change to the
+  // shared chip's gates is deliberate; update the counts along with any gate or selector
+  // intentionally added or removed
+  #[test]
+  fn chip_shape() {
+    let mut meta = ConstraintSystem::<Fp>::default();
+    let advice = [meta.advice_column(), meta.advice_column(), meta.advice_column()];
+    let instance = meta.instance_column();
+    let constant = meta.fixed_column();
+    ScalarMulConfig::configure(&mut meta, &advice, &[instance], constant);
+
+    assert_eq!(meta.num_advice_columns(), 3, "advice columns");
+    assert_eq!(meta.num_fixed_columns(), 1, "fixed columns");
+    assert_eq!(meta.num_instance_columns(), 1, "instance columns");
+    assert_eq!(meta.num_selectors(), 14, "selectors");
+    assert_eq!(meta.gates().len(), 12, "gates");
+    assert_eq!(meta.lookups().len(), 0, "lookups");
+    assert_eq!(meta.degree(), 3, "degree");
+  }
+
+  /// exposing a^2 to the first instance column and b^2 to the second
+  #[derive(Default)]
+  struct TwoInstanceCircuit {
+    a: Value<Fp>,
+    b: Value<Fp>,
+  }
+
+  impl Circuit<Fp> for TwoInstanceCircuit {
+    type Config = ScalarMulConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self { Self::default() }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+      let advice = [meta.advice_column(), meta.advice_column(), meta.advice_column()];
+      let instance = [meta.instance_column(), meta.instance_column()];
+      let constant = meta.fixed_column();
+      Self::Config::configure(meta, &advice, &instance, constant)
+    }
+
+    fn synthesize(
+      &self,
+      config: Self::Config,
+      mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+      let field_chip = ScalarMulChip::<Fp>::new(config);
+
+      let a = field_chip.load_private(layouter.namespace(|| "load a"), self.a)?;
+      let b = field_chip.load_private(layouter.namespace(|| "load b"), self.b)?;
+      let aa = field_chip.square(layouter.namespace(|| "a * a"), a)?;
+      let bb = field_chip.square(layouter.namespace(|| "b * b"), b)?;
+
+      field_chip.expose_public_in(layouter.namespace(|| "expose a^2"), aa, 0, 0)?;
+      field_chip.expose_public_in(layouter.namespace(|| "expose b^2"), bb, 1, 0)
+    }
+  }
+
+  // each output reaches the instance column it was exposed to, and not the other
+  #[test]
+  fn two_instance_columns() {
+    let (a, b) = (Fp::from(2), Fp::from(3));
+    let circuit = TwoInstanceCircuit { a: Value::known(a), b: Value::known(b) };
+    assert_satisfied(K, &circuit, vec![vec![a.square()], vec![b.square()]]);
+    assert_fails(K, &circuit, vec![vec![b.square()], vec![a.square()]]);
+  }
+
+  /// a * b, through `mul_inline` or through loads copied into `mul`
+  #[derive(Default)]
+  struct MulCircuit {
+    a:      Value<Fp>,
+    b:      Value<Fp>,
+    inline: bool,
+  }
+
+  impl Circuit<Fp> for MulCircuit {
+    type Config = ScalarMulConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self { Self { inline: self.inline, ..Self::default() } }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+      let advice = [meta.advice_column(), meta.advice_column(), meta.advice_column()];
+      let instance = meta.instance_column();
+      let constant = meta.fixed_column();
+      Self::Config::configure(meta, &advice, &[instance], constant)
+    }
+
+    fn synthesize(
+      &self,
+      config: Self::Config,
+      mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+      let chip = ScalarMulChip::<Fp>::new(config);
+      let c = if self.inline {
+        chip.mul_inline(layouter.namespace(|| "a * b"), self.a, self.b)?
+      } else {
+        let a = chip.load_private(layouter.namespace(|| "load a"), self.a)?;
+        let b = chip.load_private(layouter.namespace(|| "load b"), self.b)?;
+        chip.mul(layouter.namespace(|| "a * b"), a, b)?
+      };
+      chip.expose_public(layouter.namespace(|| "expose a * b"), c, 0)
+    }
+  }
+
+  // the region and copy savings the `mul_inline` bench trades linkage for
+  #[test]
+  fn mul_inline_copies_and_uses_less_than_mul() {
+    let [a, b] = fp([2, 3]).map(Value::known);
+    let inline = MulCircuit { a, b, inline: true };
+    let copied = MulCircuit { a, b, inline: false };
+    assert_satisfied(K, &inline, vec![vec![Fp::from(6)]]);
+
+    // only the exposed output is copied, against the two inputs too
+    assert_eq!((copy_count(&inline), copy_count(&copied)), (1, 3));
+    assert!(describe_cost(K, &inline).rows < describe_cost(K, &copied).rows);
+  }
+}
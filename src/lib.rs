@@ -2,4 +2,21 @@
 #![allow(unused_variables)]
 #![allow(dead_code)]
 
+/// The curve the benches and helpers prove over; the `fq` feature swaps in pallas, to run over the
+/// other pasta field
+#[cfg(not(feature = "fq"))]
+pub type Curve = halo2_proofs::pasta::vesta::Affine;
+/// The field circuits are built over, the scalar field of `Curve`
+#[cfg(not(feature = "fq"))]
+pub type Scalar = halo2_proofs::pasta::Fp;
+#[cfg(feature = "fq")]
+pub type Curve = halo2_proofs::pasta::pallas::Affine;
+#[cfg(feature = "fq")]
+pub type Scalar = halo2_proofs::pasta::Fq;
+
+pub mod circuits;
+pub mod cost;
 pub mod gadgets;
+pub mod prover;
+pub mod reference;
+#[cfg(any(test, feature = "testing"))] pub mod testing;
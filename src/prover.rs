@@ -0,0 +1,528 @@
+//! Helpers wrapping the halo2 prover and verifier, so benches and tests don't repeat the transcript
+//! setup
+use std::{
+  fs,
+  io::{self, Read, Write},
+  path::Path,
+  time::{Duration, Instant},
+};
+
+use blake2b_simd::Params as Blake2bParams;
+use halo2_proofs::{
+  arithmetic::CurveAffine,
+  pasta::group::ff::FromUniformBytes,
+  plonk::{
+    create_proof, keygen_pk, keygen_vk, verify_proof, BatchVerifier, Circuit, Error, ProvingKey,
+    SingleVerifier, VerifyingKey,
+  },
+  poly::commitment::Params,
+  transcript::{
+    Blake2bRead, Blake2bWrite, Challenge255, EncodedChallenge, TranscriptRead, TranscriptWrite,
+  },
+};
+use rand::{rngs::OsRng, CryptoRng, RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use rayon::prelude::*;
+
+/// Generate the params and keys for a circuit with 2^k rows
+pub fn setup<C, ConcreteCircuit>(
+  k: u32,
+  circuit: &ConcreteCircuit,
+) -> (Params<C>, ProvingKey<C>, VerifyingKey<C>)
+where
+  C: CurveAffine,
+  C::Scalar: FromUniformBytes<64>,
+  ConcreteCircuit: Circuit<C::Scalar>,
+{
+  let params = Params::new(k);
+  let (pk, vk) = keygen(&params, circuit).expect("keygen should not fail");
+  (params, pk, vk)
+}
+
+/// Generate the keys for a circuit from existing, e.g. cached, params; errors if the params don't
+/// have enough rows for the circuit
+pub fn keygen<C, ConcreteCircuit>(
+  params: &Params<C>,
+  circuit: &ConcreteCircuit,
+) -> Result<(ProvingKey<C>, VerifyingKey<C>), Error>
+where
+  C: CurveAffine,
+  C::Scalar: FromUniformBytes<64>,
+  ConcreteCircuit: Circuit<C::Scalar>,
+{
+  let vk = keygen_vk(params, circuit)?;
+  let pk = keygen_pk(params, vk.clone(), circuit)?;
+  Ok((pk, vk))
+}
+
+/// Time spent in each stage of `prove_timed`
+#[derive(Clone, Copy, Debug)]
+pub struct ProveTimings {
+  pub keygen: Duration,
+  pub proof:  Duration,
+}
+
+/// Generate the keys for `circuit` from `params` and prove it, timing each stage, for a breakdown
+/// of where proving time goes without a profiler; keygen is deterministic, so `keygen` on the same
+/// params gives the vk to verify with
+pub fn prove_timed<C, ConcreteCircuit>(
+  params: &Params<C>,
+  circuit: ConcreteCircuit,
+  public_inputs: &[&[C::Scalar]],
+) -> (Vec<u8>, ProveTimings)
+where
+  C: CurveAffine,
+  C::Scalar: FromUniformBytes<64>,
+  ConcreteCircuit: Circuit<C::Scalar>,
+{
+  let start = Instant::now();
+  let (pk, _) = keygen(params, &circuit).expect("keygen should not fail");
+  let keygen = start.elapsed();
+
+  let start = Instant::now();
+  let proof = prove(params, &pk, circuit, public_inputs);
+  (proof, ProveTimings { keygen, proof: start.elapsed() })
+}
+
+/// Prove `circuit`, with one slice of `public_inputs` per instance column, returning the proof
+/// bytes
+pub fn prove<C, ConcreteCircuit>(
+  params: &Params<C>,
+  pk: &ProvingKey<C>,
+  circuit: ConcreteCircuit,
+  public_inputs: &[&[C::Scalar]],
+) -> Vec<u8>
+where
+  C: CurveAffine,
+  C::Scalar: FromUniformBytes<64>,
+  ConcreteCircuit: Circuit<C::Scalar>,
+{
+  prove_with_rng(params, pk, circuit, public_inputs, OsRng)
+}
+
+/// `prove`, blinding with a ChaCha20 rng seeded by `seed`, so equal seeds give byte-identical
+/// proofs
+pub fn prove_seeded<C, ConcreteCircuit>(
+  params: &Params<C>,
+  pk: &ProvingKey<C>,
+  circuit: ConcreteCircuit,
+  public_inputs: &[&[C::Scalar]],
+  seed: u64,
+) -> Vec<u8>
+where
+  C: CurveAffine,
+  C::Scalar: FromUniformBytes<64>,
+  ConcreteCircuit: Circuit<C::Scalar>,
+{
+  prove_with_rng(params, pk, circuit, public_inputs, ChaCha20Rng::seed_from_u64(seed))
+}
+
+/// `prove`, drawing the proof's blinding factors from `rng`
+pub fn prove_with_rng<C, ConcreteCircuit>(
+  params: &Params<C>,
+  pk: &ProvingKey<C>,
+  circuit: ConcreteCircuit,
+  public_inputs: &[&[C::Scalar]],
+  rng: impl RngCore + CryptoRng,
+) -> Vec<u8>
+where
+  C: CurveAffine,
+  C::Scalar: FromUniformBytes<64>,
+  ConcreteCircuit: Circuit<C::Scalar>,
+{
+  // ref: https://github.com/zcash/halo2/blob/76b3f892a9d598923bbb5a747701fff44ae4c0ea/halo2_gadgets/benches/poseidon.rs#L178
+  // choose a hash function for FS challenges
+  // Why blake2b not poseidon?
+  // > We will replace BLAKE2b with an algebraic hash function in a later version. - Halo 2 authors
+  let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+  create_proof(params, pk, &[circuit], &[public_inputs], rng, &mut transcript)
+    .expect("proof generation should not fail");
+  transcript.finalize()
+}
+
+/// `prove`, writing into any transcript rather than Blake2b; the caller finalizes it into bytes
+pub fn prove_with_transcript<C, E, T, ConcreteCircuit>(
+  params: &Params<C>,
+  pk: &ProvingKey<C>,
+  circuit: ConcreteCircuit,
+  public_inputs: &[&[C::Scalar]],
+  transcript: &mut T,
+) where
+  C: CurveAffine,
+  C::Scalar: FromUniformBytes<64>,
+  E: EncodedChallenge<C>,
+  T: TranscriptWrite<C, E>,
+  ConcreteCircuit: Circuit<C::Scalar>,
+{
+  create_proof(params, pk, &[circuit], &[public_inputs], OsRng, transcript)
+    .expect("proof generation should not fail");
+}
+
+/// Verify `proof` against `public_inputs`, with one slice per instance column
+pub fn verify<C>(
+  params: &Params<C>,
+  vk: &VerifyingKey<C>,
+  public_inputs: &[&[C::Scalar]],
+  proof: &[u8],
+) -> Result<(), Error>
+where
+  C: CurveAffine,
+  C::Scalar: FromUniformBytes<64>,
+{
+  let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(proof);
+  verify_with_transcript(params, vk, public_inputs, &mut transcript)
+}
+
+/// `verify`, reading the proof from any transcript rather than Blake2b
+pub fn verify_with_transcript<C, E, T>(
+  params: &Params<C>,
+  vk: &VerifyingKey<C>,
+  public_inputs: &[&[C::Scalar]],
+  transcript: &mut T,
+) -> Result<(), Error>
+where
+  C: CurveAffine,
+  C::Scalar: FromUniformBytes<64>,
+  E: EncodedChallenge<C>,
+  T: TranscriptRead<C, E>,
+{
+  let strategy = SingleVerifier::new(params);
+  verify_proof(params, vk, strategy, &[public_inputs], transcript)
+}
+
+/// Run the whole pipeline, keygen, prove and verify, for a smoke test; false rather than a panic
+/// if the circuit doesn't fit in 2^k rows, the prover rejects it or its public inputs, or the proof
+/// doesn't verify
+pub fn prove_and_verify<C, ConcreteCircuit>(
+  k: u32,
+  circuit: ConcreteCircuit,
+  public_inputs: &[&[C::Scalar]],
+) -> bool
+where
+  C: CurveAffine,
+  C::Scalar: FromUniformBytes<64>,
+  ConcreteCircuit: Circuit<C::Scalar>,
+{
+  let params = Params::new(k);
+  let Ok((pk, vk)) = keygen(&params, &circuit) else { return false };
+
+  // not `prove`, which panics where this should return false
+  let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+  if create_proof(&params, &pk, &[circuit], &[public_inputs], OsRng, &mut transcript).is_err() {
+    return false;
+  }
+  verify(&params, &vk, public_inputs, &transcript.finalize()).is_ok()
+}
+
+/// Prove every circuit in `circuits` in a single proof, with one entry of `public_inputs` per
+/// circuit, each holding one slice per instance column
+pub fn prove_batch<C, ConcreteCircuit>(
+  params: &Params<C>,
+  pk: &ProvingKey<C>,
+  circuits: &[ConcreteCircuit],
+  public_inputs: &[&[&[C::Scalar]]],
+) -> Vec<u8>
+where
+  C: CurveAffine,
+  C::Scalar: FromUniformBytes<64>,
+  ConcreteCircuit: Circuit<C::Scalar>,
+{
+  assert_eq!(circuits.len(), public_inputs.len(), "need one set of public inputs per circuit");
+  let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+  create_proof(params, pk, circuits, public_inputs, OsRng, &mut transcript)
+    .expect("proof generation should not fail");
+  transcript.finalize()
+}
+
+/// Prove each circuit in `circuits` separately, in parallel, with one entry of `public_inputs` per
+/// circuit; each proof gets its own transcript and rng, so the proofs are independent
+pub fn prove_parallel<C, ConcreteCircuit>(
+  params: &Params<C>,
+  pk: &ProvingKey<C>,
+  circuits: &[ConcreteCircuit],
+  public_inputs: &[&[&[C::Scalar]]],
+) -> Vec<Vec<u8>>
+where
+  C: CurveAffine,
+  C::Scalar: FromUniformBytes<64>,
+  ConcreteCircuit: Circuit<C::Scalar> + Clone + Sync,
+{
+  assert_eq!(circuits.len(), public_inputs.len(), "need one set of public inputs per circuit");
+  circuits
+    .par_iter()
+    .zip(public_inputs.par_iter())
+    .map(|(circuit, public_inputs)| prove(params, pk, circuit.clone(), *public_inputs))
+    .collect()
+}
+
+/// Verify a proof made by `prove_batch` against the same per-circuit `public_inputs`
+pub fn verify_batch<C>(
+  params: &Params<C>,
+  vk: &VerifyingKey<C>,
+  public_inputs: &[&[&[C::Scalar]]],
+  proof: &[u8],
+) -> Result<(), Error>
+where
+  C: CurveAffine,
+  C::Scalar: FromUniformBytes<64>,
+{
+  let strategy = SingleVerifier::new(params);
+  let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(proof);
+  verify_proof(params, vk, strategy, public_inputs, &mut transcript)
+}
+
+/// Verify many single-circuit proofs at once, cheaper than one by one, with one entry of
+/// `public_inputs` per proof; true iff every proof verifies
+pub fn verify_many<C>(
+  params: &Params<C>,
+  vk: &VerifyingKey<C>,
+  public_inputs: &[&[&[C::Scalar]]],
+  proofs: &[Vec<u8>],
+) -> bool
+where
+  C: CurveAffine,
+  C::Scalar: FromUniformBytes<64>,
+{
+  assert_eq!(proofs.len(), public_inputs.len(), "need one set of public inputs per proof");
+  let mut batch = BatchVerifier::new();
+  for (proof, public_inputs) in proofs.iter().zip(public_inputs) {
+    // instances are indexed by circuit, then column; each of these proofs has one circuit
+    let instances = vec![public_inputs.iter().map(|column| column.to_vec()).collect()];
+    batch.add_proof(instances, proof.clone());
+  }
+  batch.finalize(params, vk)
+}
+
+/// A 32-byte blake2b hash of `vk`, to tell whether a regenerated vk still matches the circuit.
+/// `halo2_proofs` 0.3 can't serialize a vk, so this hashes the `Debug` output of its pinned
+/// representation, covering k, the constraint system and the fixed commitments. `Debug` output is
+/// not a stable encoding, so fingerprints compare only within one build: the same `halo2_proofs`
+/// and the same rustc. Don't ship one, or compare it against one recorded by another build
+pub fn vk_fingerprint<C: CurveAffine>(vk: &VerifyingKey<C>) -> [u8; 32] {
+  let pinned = format!("{:?}", vk.pinned());
+  let hash = Blake2bParams::new().hash_length(32).hash(pinned.as_bytes());
+  hash.as_bytes().try_into().expect("hash length is 32")
+}
+
+/// Identifies a proof file written by `write_proof`
+const PROOF_MAGIC: [u8; 4] = *b"H2PF";
+
+/// Persist `proof` to `path`, behind a header of the magic number and the `k` it was proven with
+pub fn write_proof(path: &Path, k: u32, proof: &[u8]) -> io::Result<()> {
+  let mut file = fs::File::create(path)?;
+  file.write_all(&PROOF_MAGIC)?;
+  file.write_all(&k.to_le_bytes())?;
+  file.write_all(proof)
+}
+
+/// Read back a proof written by `write_proof`, returning the `k` it was proven with and its bytes
+pub fn read_proof(path: &Path) -> io::Result<(u32, Vec<u8>)> {
+  let mut file = fs::File::open(path)?;
+
+  let mut magic = [0u8; 4];
+  file.read_exact(&mut magic)?;
+  if magic != PROOF_MAGIC {
+    return Err(io::Error::new(io::ErrorKind::InvalidData, "not a proof file: bad magic number"));
+  }
+
+  let mut k = [0u8; 4];
+  file.read_exact(&mut k)?;
+
+  let mut proof = Vec::new();
+  file.read_to_end(&mut proof)?;
+  Ok((u32::from_le_bytes(k), proof))
+}
+
+/// Identifies a params file written by `write_params`
+const PARAMS_MAGIC: [u8; 4] = *b"H2PM";
+
+/// Persist `params` to `path`, behind a magic number. `halo2_proofs` 0.3 can't serialize verifying
+/// or proving keys, but keygen is deterministic, so keys regenerated from cached params match
+pub fn write_params<C: CurveAffine>(path: &Path, params: &Params<C>) -> io::Result<()> {
+  let mut file = fs::File::create(path)?;
+  file.write_all(&PARAMS_MAGIC)?;
+  params.write(&mut file)
+}
+
+/// Read back params written by `write_params`
+pub fn read_params<C: CurveAffine>(path: &Path) -> io::Result<Params<C>> {
+  let mut file = fs::File::open(path)?;
+
+  let mut magic = [0u8; 4];
+  file.read_exact(&mut magic)?;
+  if magic != PARAMS_MAGIC {
+    return Err(io::Error::new(io::ErrorKind::InvalidData, "not a params file: bad magic number"));
+  }
+
+  Params::read(&mut file)
+}
+
+#[cfg(test)]
+mod tests {
+  use std::{env, path::PathBuf, process};
+
+  use halo2_proofs::pasta::{group::ff::Field, vesta, Fp};
+
+  use super::*;
+  use crate::circuits::nn_mul::NNMulCircuit;
+
+  const K: u32 = 4;
+
+  // a path in the temp dir unique to this process and `name`, so parallel tests don't collide
+  fn temp_path(name: &str) -> PathBuf {
+    env::temp_dir().join(format!("halo-2-benches-{}-{name}", process::id()))
+  }
+
+  // three `NNMulCircuit`s with their outputs
+  fn three_circuits() -> (Vec<NNMulCircuit<Fp>>, Vec<Fp>) {
+    [(2, 3), (4, 5), (6, 7)].into_iter().map(|(a, b)| NNMulCircuit::from_u64s(a, b)).unzip()
+  }
+
+  #[test]
+  fn prove_then_verify() {
+    let (circuit, c) = NNMulCircuit::new(Fp::from(2), Fp::from(3));
+    let (params, pk, vk) = setup::<vesta::Affine, _>(K, &circuit);
+    let proof = prove(&params, &pk, circuit, &[&[c]]);
+
+    assert!(!proof.is_empty());
+    assert!(verify(&params, &vk, &[&[c]], &proof).is_ok());
+    assert!(verify(&params, &vk, &[&[c + Fp::ONE]], &proof).is_err());
+  }
+
+  #[test]
+  fn tampered_proof_fails() {
+    let (circuit, c) = NNMulCircuit::new(Fp::from(2), Fp::from(3));
+    let (params, pk, vk) = setup::<vesta::Affine, _>(K, &circuit);
+    let proof = prove(&params, &pk, circuit, &[&[c]]);
+
+    // a few bytes spread across the proof, so no single lucky position decides the outcome
+    for i in [0, proof.len() / 3, proof.len() / 2, proof.len() - 1] {
+      let mut tampered = proof.clone();
+      tampered[i] ^= 1;
+      assert!(verify(&params, &vk, &[&[c]], &tampered).is_err(), "flipped byte {i} was accepted");
+    }
+  }
+
+  #[test]
+  fn seeded_proofs_repeat() {
+    let (circuit, c) = NNMulCircuit::new(Fp::from(2), Fp::from(3));
+    let (params, pk, _) = setup::<vesta::Affine, _>(K, &circuit);
+    let prove = |seed| prove_seeded(&params, &pk, circuit.clone(), &[&[c]], seed);
+
+    assert_eq!(prove(7), prove(7), "equal seeds should give byte-identical proofs");
+    assert_ne!(prove(7), prove(8), "different seeds should blind differently");
+  }
+
+  #[test]
+  fn prove_and_verify_reports_failure() {
+    let (circuit, c) = NNMulCircuit::new(Fp::from(2), Fp::from(3));
+    assert!(prove_and_verify::<vesta::Affine, _>(K, circuit.clone(), &[&[c]]));
+    assert!(!prove_and_verify::<vesta::Affine, _>(K, circuit.clone(), &[&[c + Fp::ONE]]));
+    // too few rows for the circuit
+    assert!(!prove_and_verify::<vesta::Affine, _>(2, circuit.clone(), &[&[c]]));
+    // public inputs for two instance columns, where the circuit has one
+    assert!(!prove_and_verify::<vesta::Affine, _>(K, circuit, &[&[c], &[c]]));
+  }
+
+  #[test]
+  fn batch_proof_verifies() {
+    let (circuits, outputs) = three_circuits();
+    let (params, pk, vk) = setup::<vesta::Affine, _>(K, &circuits[0]);
+    let public_inputs: Vec<[&[Fp]; 1]> =
+      outputs.iter().map(|c| [std::slice::from_ref(c)]).collect();
+    let public_inputs: Vec<&[&[Fp]]> = public_inputs.iter().map(|c| c.as_slice()).collect();
+    let proof = prove_batch(&params, &pk, &circuits, &public_inputs);
+
+    assert!(verify_batch(&params, &vk, &public_inputs, &proof).is_ok());
+    let mut swapped = public_inputs.clone();
+    swapped.swap(0, 1);
+    assert!(verify_batch(&params, &vk, &swapped, &proof).is_err());
+  }
+
+  #[test]
+  fn parallel_proofs_verify_singly_and_together() {
+    let (circuits, outputs) = three_circuits();
+    let (params, pk, vk) = setup::<vesta::Affine, _>(K, &circuits[0]);
+    let public_inputs: Vec<[&[Fp]; 1]> =
+      outputs.iter().map(|c| [std::slice::from_ref(c)]).collect();
+    let public_inputs: Vec<&[&[Fp]]> = public_inputs.iter().map(|c| c.as_slice()).collect();
+    let mut proofs = prove_parallel(&params, &pk, &circuits, &public_inputs);
+
+    for (proof, public_inputs) in proofs.iter().zip(&public_inputs) {
+      assert!(verify(&params, &vk, public_inputs, proof).is_ok());
+    }
+    assert!(verify_many(&params, &vk, &public_inputs, &proofs));
+
+    let mid = proofs[1].len() / 2;
+    proofs[1][mid] ^= 1;
+    assert!(!verify_many(&params, &vk, &public_inputs, &proofs), "corrupted proof was accepted");
+  }
+
+  #[test]
+  fn proof_file_round_trips() {
+    let path = temp_path("proof");
+    let proof = vec![1, 2, 3, 4, 5];
+    write_proof(&path, K, &proof).unwrap();
+    let read = read_proof(&path);
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(read.unwrap(), (K, proof));
+  }
+
+  #[test]
+  fn proof_file_checks_magic_and_length() {
+    let path = temp_path("not-a-proof");
+    fs::write(&path, b"H2PX\x04\x00\x00\x00proof").unwrap();
+    let bad_magic = read_proof(&path);
+    // the magic, but cut off partway through k
+    fs::write(&path, b"H2PF\x04\x00").unwrap();
+    let truncated = read_proof(&path);
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(bad_magic.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    assert_eq!(truncated.unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+  }
+
+  #[test]
+  fn keys_from_cached_params_verify() {
+    let (circuit, c) = NNMulCircuit::new(Fp::from(2), Fp::from(3));
+    let (params, _, vk) = setup::<vesta::Affine, _>(K, &circuit);
+
+    let path = temp_path("params");
+    write_params(&path, &params).unwrap();
+    let read = read_params::<vesta::Affine>(&path);
+    // a proof file has the wrong magic
+    write_proof(&path, K, &[0; 8]).unwrap();
+    let bad_magic = read_params::<vesta::Affine>(&path);
+    fs::remove_file(&path).unwrap();
+
+    let cached = read.unwrap();
+    let (pk, _) = keygen(&cached, &circuit).unwrap();
+    let proof = prove(&cached, &pk, circuit, &[&[c]]);
+    assert!(verify(&params, &vk, &[&[c]], &proof).is_ok(), "keys from cached params disagree");
+    assert_eq!(bad_magic.err().map(|e| e.kind()), Some(io::ErrorKind::InvalidData));
+  }
+
+  // fingerprints compare within one build, so only their equality across keygens is checked, never
+  // a recorded value
+  #[test]
+  fn vk_fingerprint_tracks_k() {
+    let (circuit, _) = NNMulCircuit::new(Fp::from(2), Fp::from(3));
+    let fingerprint = |k| vk_fingerprint(&setup::<vesta::Affine, _>(k, &circuit).2);
+
+    assert_eq!(fingerprint(K), fingerprint(K), "keygen at the same k should give the same vk");
+    assert_ne!(fingerprint(K), fingerprint(K + 1), "vks at different k should differ");
+  }
+
+  // both stages timed, and the proof verifies against a vk regenerated from the same params
+  #[test]
+  fn prove_timed_times_both_stages() {
+    let (circuit, c) = NNMulCircuit::new(Fp::from(2), Fp::from(3));
+    let params = Params::<vesta::Affine>::new(K);
+    let (proof, timings) = prove_timed(&params, circuit.clone(), &[&[c]]);
+    assert!(!timings.keygen.is_zero() && !timings.proof.is_zero(), "{timings:?}");
+
+    let (_, vk) = keygen(&params, &circuit).expect("keygen should not fail");
+    assert!(verify(&params, &vk, &[&[c]], &proof).is_ok(), "timed proof should verify");
+  }
+}
@@ -0,0 +1,18 @@
+//! Plain-Rust reference implementations of the chip instructions, to cross-check circuit outputs
+//! against a trusted computation
+use halo2_proofs::pasta::group::ff::Field;
+
+/// a * b
+pub fn mul<F: Field>(a: F, b: F) -> F { a * b }
+
+/// a + b
+pub fn add<F: Field>(a: F, b: F) -> F { a + b }
+
+/// the sum of pairwise products of `a` and `b`, which must have the same length
+pub fn dot<F: Field>(a: &[F], b: &[F]) -> F {
+  assert_eq!(a.len(), b.len(), "dot needs equal-length inputs");
+  a.iter().zip(b).fold(F::ZERO, |acc, (a, b)| add(acc, mul(*a, *b)))
+}
+
+/// base^exp
+pub fn pow<F: Field>(base: F, exp: u64) -> F { base.pow_vartime([exp]) }
@@ -0,0 +1,405 @@
+//! Assertion helpers for gadget tests, running circuits through the halo2 `MockProver`
+use halo2_proofs::{
+  circuit::{Chip, Layouter, SimpleFloorPlanner, Value},
+  dev::MockProver,
+  pasta::{group::ff::Field, Fp},
+  plonk::{Circuit, ConstraintSystem, Error},
+};
+
+use crate::{
+  circuits::nn_mul::NNMulCircuit,
+  gadgets::scalar_mul::{
+    InstanceWriter, Number, ScalarMulChip, ScalarMulConfig, ScalarMulInstructions,
+  },
+};
+
+/// Assert that `circuit` satisfies every constraint, given one vec of `public_inputs` per instance
+/// column
+pub fn assert_satisfied<C: Circuit<Fp>>(k: u32, circuit: &C, public_inputs: Vec<Vec<Fp>>) {
+  let prover = MockProver::run(k, circuit, public_inputs).expect("synthesis should not fail");
+  assert_eq!(prover.verify(), Ok(()));
+}
+
+/// Assert that `circuit` synthesizes, but violates at least one constraint
+pub fn assert_fails<C: Circuit<Fp>>(k: u32, circuit: &C, public_inputs: Vec<Vec<Fp>>) {
+  let prover = MockProver::run(k, circuit, public_inputs).expect("synthesis should not fail");
+  assert!(prover.verify().is_err(), "expected a constraint to fail");
+}
+
+/// Assert that `circuit` refuses to synthesize with `Error::Synthesis`, as instructions do given
+/// inputs known to be invalid
+pub fn assert_synthesis_fails<C: Circuit<Fp>>(k: u32, circuit: &C, public_inputs: Vec<Vec<Fp>>) {
+  let result = MockProver::run(k, circuit, public_inputs);
+  assert!(matches!(result, Err(Error::Synthesis)), "expected synthesis to fail");
+}
+
+/// Describe the first difference between two instance columns, with values in hex
+pub fn diff_instances(expected: &[Fp], got: &[Fp]) -> String {
+  match expected.iter().zip(got).position(|(expected, got)| expected != got) {
+    Some(i) => format!("instance {i}: expected {:?}, got {:?}", expected[i], got[i]),
+    None if expected.len() != got.len() =>
+      format!("instance length: expected {}, got {}", expected.len(), got.len()),
+    None => String::from("instances match"),
+  }
+}
+
+/// A circuit paired with its public outputs as computed natively, so a test checks the circuit
+/// against its spec rather than against numbers worked out by hand
+#[derive(Clone)]
+pub struct SelfCheckingCircuit<C> {
+  pub circuit:  C,
+  /// the reference public outputs, for the first instance column
+  pub expected: Vec<Fp>,
+}
+
+impl<C: Circuit<Fp>> SelfCheckingCircuit<C> {
+  pub fn new(circuit: C, reference: impl FnOnce() -> Vec<Fp>) -> Self {
+    Self { circuit, expected: reference() }
+  }
+
+  /// Assert that, over 2^k rows, the circuit exposes exactly the reference outputs
+  pub fn check(&self, k: u32) { assert_satisfied(k, &self.circuit, vec![self.expected.clone()]); }
+}
+
+impl SelfCheckingCircuit<NNMulCircuit<Fp>> {
+  /// `NNMulCircuit` against the reference a^2 * b^2
+  pub fn nn_mul(a: Fp, b: Fp) -> Self {
+    let (circuit, claimed) = NNMulCircuit::new(a, b);
+    let checked = Self::new(circuit, || vec![a.square() * b.square()]);
+    // the builder's own output is what callers expose, so it must agree with the reference too
+    assert!(checked.expected == [claimed], "{}", diff_instances(&checked.expected, &[claimed]));
+    checked
+  }
+}
+
+impl<C: Circuit<Fp>> Circuit<Fp> for SelfCheckingCircuit<C> {
+  type Config = C::Config;
+  type FloorPlanner = C::FloorPlanner;
+
+  fn without_witnesses(&self) -> Self {
+    Self { circuit: self.circuit.without_witnesses(), expected: self.expected.clone() }
+  }
+
+  fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config { C::configure(meta) }
+
+  fn synthesize(&self, config: Self::Config, layouter: impl Layouter<Fp>) -> Result<(), Error> {
+    self.circuit.synthesize(config, layouter)
+  }
+}
+
+/// A `ScalarMulChip` instruction, for `OpCircuit` to apply to its inputs
+#[derive(Clone, Copy, Debug)]
+pub enum Op {
+  /// the first input as loaded, checking that `Number::value` reads it back
+  Load,
+  /// `load_private_vec` of every input, checking that each value reads back
+  LoadPrivateVec,
+  /// `assign_bulk` of every input, down the advice column at this index
+  AssignBulk(usize),
+  /// `load_private_with_value` of the first input, checking the value handed back
+  LoadPrivateWithValue,
+  LoadConstant(Fp),
+  Zero,
+  One,
+  /// `load_instance` of this row
+  LoadInstance(usize),
+  /// the first input times `load_public_constant` of this row
+  MulByPublicConstant(usize),
+  Mul,
+  /// `mul` of the first input by itself, the naive square
+  MulSelf,
+  /// `mul_inline` on the values of the first two inputs
+  MulInline,
+  Add,
+  Sub,
+  Square,
+  Sqrt,
+  Neg,
+  Inverse,
+  Div,
+  MulByConstant(Fp),
+  AddConstant(Fp),
+  MulAdd,
+  /// the first half of the inputs times the second, element-wise
+  MulMany,
+  /// the first half of the inputs dotted with the second
+  Dot,
+  Sum,
+  Product,
+  AssertBool,
+  Select,
+  IsZero,
+  IsEqual,
+  AssertEqual,
+  AssertEqualConstant(Fp),
+  Pow(u64),
+  RangeCheck(usize),
+  /// `range_check_lookup`, after loading the range table
+  RangeCheckLookup(usize),
+  ToBits(usize),
+  /// the first input times the second, through `to_bits` of this many bits then `scalar_mul_bits`
+  ScalarMulBits(usize),
+  LessThan(usize),
+  Max,
+  Min,
+  Relu(usize),
+  /// the first input times each of the rest, through `mul_chained`
+  MulChained,
+  /// `fold_mul` of the rest of the inputs into the first, exposing the final accumulator and then
+  /// each intermediate one
+  FoldMul,
+  /// `horner` with all but the last input as coefficients, at the last
+  Horner,
+  AssertZeroIf,
+  AssertNonzero,
+  /// `square_and_expose` of the first input to row 0
+  SquareAndExpose,
+  /// `compute_and_expose` of the product of the first two inputs to row 0
+  ComputeAndExpose,
+  /// each input in turn through an `InstanceWriter`
+  ExposeNext,
+  /// `expose_public_vec` of every input from the first row, to a column with the second's rows
+  ExposePublicVec(usize, usize),
+}
+
+impl Op {
+  // apply the instruction to `x`, the loaded `inputs`, returning the outputs to expose
+  fn apply(
+    self,
+    chip: &ScalarMulChip<Fp>,
+    mut layouter: impl Layouter<Fp>,
+    inputs: &[Value<Fp>],
+    x: &[Number<Fp>],
+  ) -> Result<Vec<Number<Fp>>, Error> {
+    let arg = |i: usize| x[i].clone();
+    let (lhs, rhs) = x.split_at(x.len() / 2);
+    let outputs = match self {
+      Op::Load => {
+        check_read_back(arg(0).value(), inputs[0])?;
+        vec![arg(0)]
+      },
+      Op::LoadPrivateVec => {
+        let nums = chip.load_private_vec(layouter, inputs)?;
+        for (num, input) in nums.iter().zip(inputs) {
+          check_read_back(num.value(), *input)?;
+        }
+        nums
+      },
+      Op::AssignBulk(column) => chip.assign_bulk(layouter, inputs, column)?,
+      Op::LoadPrivateWithValue => {
+        let (num, value) = chip.load_private_with_value(layouter, inputs[0])?;
+        check_read_back(value, inputs[0])?;
+        vec![num]
+      },
+      Op::LoadConstant(c) => vec![chip.load_constant(layouter, c)?],
+      Op::Zero => vec![chip.zero(layouter)?],
+      Op::One => vec![chip.one(layouter)?],
+      Op::LoadInstance(row) => vec![chip.load_instance(layouter, row)?],
+      Op::MulByPublicConstant(row) => {
+        let c = chip.load_public_constant(layouter.namespace(|| "load c"), row)?;
+        vec![chip.mul(layouter.namespace(|| "x * c"), arg(0), c)?]
+      },
+      Op::Mul => vec![chip.mul(layouter, arg(0), arg(1))?],
+      Op::MulSelf => vec![chip.mul(layouter, arg(0), arg(0))?],
+      Op::MulInline => vec![chip.mul_inline(layouter, arg(0).value(), arg(1).value())?],
+      Op::Add => vec![chip.add(layouter, arg(0), arg(1))?],
+      Op::Sub => vec![chip.sub(layouter, arg(0), arg(1))?],
+      Op::Square => vec![chip.square(layouter, arg(0))?],
+      Op::Sqrt => vec![chip.sqrt(layouter, arg(0))?],
+      Op::Neg => vec![chip.neg(layouter, arg(0))?],
+      Op::Inverse => vec![chip.inverse(layouter, arg(0))?],
+      Op::Div => vec![chip.div(layouter, arg(0), arg(1))?],
+      Op::MulByConstant(c) => vec![chip.mul_by_constant(layouter, arg(0), c)?],
+      Op::AddConstant(c) => vec![chip.add_constant(layouter, arg(0), c)?],
+      Op::MulAdd => vec![chip.mul_add(layouter, arg(0), arg(1), arg(2))?],
+      Op::MulMany => chip.mul_many(layouter, lhs, rhs)?,
+      Op::Dot => vec![chip.dot(layouter, lhs, rhs)?],
+      Op::Sum => vec![chip.sum(layouter, x)?],
+      Op::Product => vec![chip.product(layouter, x)?],
+      Op::AssertBool => {
+        chip.assert_bool(layouter, arg(0))?;
+        vec![]
+      },
+      Op::Select => vec![chip.select(layouter, arg(0), arg(1), arg(2))?],
+      Op::IsZero => vec![chip.is_zero(layouter, arg(0))?],
+      Op::IsEqual => vec![chip.is_equal(layouter, arg(0), arg(1))?],
+      Op::AssertEqual => {
+        chip.assert_equal(layouter, arg(0), arg(1))?;
+        vec![]
+      },
+      Op::AssertEqualConstant(c) => {
+        chip.assert_equal_constant(layouter, arg(0), c)?;
+        vec![]
+      },
+      Op::Pow(exp) => vec![chip.pow(layouter, arg(0), exp)?],
+      Op::RangeCheck(n_bits) => {
+        chip.range_check(layouter, arg(0), n_bits)?;
+        vec![]
+      },
+      Op::RangeCheckLookup(n_bits) => {
+        chip.load_range_table(layouter.namespace(|| "range table"))?;
+        chip.range_check_lookup(layouter.namespace(|| "range check"), arg(0), n_bits)?;
+        vec![]
+      },
+      Op::ToBits(n_bits) => chip.to_bits(layouter, arg(0), n_bits)?,
+      Op::ScalarMulBits(n_bits) => {
+        let bits = chip.to_bits(layouter.namespace(|| "scalar bits"), arg(1), n_bits)?;
+        vec![chip.scalar_mul_bits(layouter.namespace(|| "scalar * base"), arg(0), &bits)?]
+      },
+      Op::LessThan(n_bits) => vec![chip.less_than(layouter, arg(0), arg(1), n_bits)?],
+      Op::Max => vec![chip.max(layouter, arg(0), arg(1))?],
+      Op::Min => vec![chip.min(layouter, arg(0), arg(1))?],
+      Op::Relu(n_bits) => vec![chip.relu(layouter, arg(0), n_bits)?],
+      Op::MulChained => vec![chip.mul_chained(layouter, arg(0), &x[1..])?],
+      Op::FoldMul => {
+        let (acc, intermediates) = chip.fold_mul(layouter, arg(0), &x[1..])?;
+        [vec![acc], intermediates].concat()
+      },
+      Op::Horner => {
+        let (at, coeffs) = x.split_last().expect("horner needs a point to evaluate at");
+        vec![chip.horner(layouter, coeffs, at.clone())?]
+      },
+      Op::AssertZeroIf => {
+        chip.assert_zero_if(layouter, arg(0), arg(1))?;
+        vec![]
+      },
+      Op::AssertNonzero => {
+        chip.assert_nonzero(layouter, arg(0))?;
+        vec![]
+      },
+      Op::SquareAndExpose => {
+        chip.square_and_expose(layouter, arg(0), 0)?;
+        vec![]
+      },
+      Op::ComputeAndExpose => {
+        let c = chip.mul(layouter.namespace(|| "a * b"), arg(0), arg(1))?;
+        chip.compute_and_expose(layouter.namespace(|| "expose a * b"), c, 0)?;
+        vec![]
+      },
+      Op::ExposePublicVec(start_row, instance_rows) => {
+        chip.expose_public_vec(layouter, x, start_row, instance_rows)?;
+        vec![]
+      },
+      Op::ExposeNext => {
+        let mut output = InstanceWriter::new(chip.config().instance[0]);
+        for (i, num) in x.iter().enumerate() {
+          output.expose_next(layouter.namespace(|| format!("expose {i}")), num.clone())?;
+        }
+        vec![]
+      },
+    };
+    Ok(outputs)
+  }
+}
+
+// fail synthesis if a value read back from the chip isn't the one assigned
+fn check_read_back(got: Value<Fp>, assigned: Value<Fp>) -> Result<(), Error> {
+  got.zip(assigned).error_if_known_and(|(got, assigned)| got != assigned)
+}
+
+/// A circuit loading each of `inputs` privately, applying `op` to them and exposing its outputs to
+/// consecutive rows of the instance column, to test one instruction in isolation. The chip is built
+/// `with_range_table`, whose 256 rows `Op::RangeCheckLookup` needs k of at least 9 to fit
+#[derive(Clone, Debug)]
+pub struct OpCircuit {
+  pub op:     Op,
+  pub inputs: Vec<Value<Fp>>,
+}
+
+impl OpCircuit {
+  pub fn new(op: Op, inputs: &[Fp]) -> Self {
+    Self { op, inputs: inputs.iter().copied().map(Value::known).collect() }
+  }
+}
+
+impl Circuit<Fp> for OpCircuit {
+  type Config = ScalarMulConfig;
+  type FloorPlanner = SimpleFloorPlanner;
+
+  // the op and the number of inputs fix the layout, so keep them when dropping the witnesses
+  fn without_witnesses(&self) -> Self {
+    Self { op: self.op, inputs: vec![Value::unknown(); self.inputs.len()] }
+  }
+
+  fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+    let advice = [meta.advice_column(), meta.advice_column(), meta.advice_column()];
+    let instance = meta.instance_column();
+    let constant = meta.fixed_column();
+    Self::Config::configure(meta, &advice, &[instance], constant).with_range_table(meta)
+  }
+
+  fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+    let field_chip = ScalarMulChip::<Fp>::new(config);
+
+    let x = self
+      .inputs
+      .iter()
+      .enumerate()
+      .map(|(i, x)| field_chip.load_private(layouter.namespace(|| format!("load input {i}")), *x))
+      .collect::<Result<Vec<_>, _>>()?;
+    let name = format!("{:?}", self.op);
+    let outputs = self.op.apply(&field_chip, layouter.namespace(|| name), &self.inputs, &x)?;
+
+    for (i, output) in outputs.into_iter().enumerate() {
+      field_chip.expose_public(layouter.namespace(|| format!("expose output {i}")), output, i)?;
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const K: u32 = 4;
+
+  #[test]
+  fn satisfied_accepts_the_true_output() {
+    let (circuit, c) = NNMulCircuit::new(Fp::from(2), Fp::from(3));
+    assert_satisfied(K, &circuit, vec![vec![c]]);
+  }
+
+  #[test]
+  #[should_panic]
+  fn satisfied_panics_on_a_wrong_output() {
+    let (circuit, c) = NNMulCircuit::new(Fp::from(2), Fp::from(3));
+    assert_satisfied(K, &circuit, vec![vec![c + Fp::ONE]]);
+  }
+
+  #[test]
+  fn fails_catches_a_wrong_output() {
+    let (circuit, c) = NNMulCircuit::new(Fp::from(2), Fp::from(3));
+    assert_fails(K, &circuit, vec![vec![c + Fp::ONE]]);
+  }
+
+  #[test]
+  #[should_panic(expected = "expected a constraint to fail")]
+  fn fails_panics_on_the_true_output() {
+    let (circuit, c) = NNMulCircuit::new(Fp::from(2), Fp::from(3));
+    assert_fails(K, &circuit, vec![vec![c]]);
+  }
+
+  #[test]
+  fn self_checking_nn_mul() {
+    SelfCheckingCircuit::nn_mul(Fp::from(2), Fp::from(3)).check(K);
+    SelfCheckingCircuit::nn_mul(Fp::ZERO, -Fp::ONE).check(K);
+  }
+
+  #[test]
+  #[should_panic]
+  fn self_checking_panics_on_a_wrong_reference() {
+    let (circuit, c) = NNMulCircuit::new(Fp::from(2), Fp::from(3));
+    SelfCheckingCircuit::new(circuit, || vec![c + Fp::ONE]).check(K);
+  }
+
+  #[test]
+  fn diff_instances_names_the_first_difference() {
+    let [a, b, c] = [1, 2, 3].map(Fp::from);
+    assert_eq!(diff_instances(&[a, b], &[a, b]), "instances match");
+    assert_eq!(
+      diff_instances(&[a, b, c], &[a, c, b]),
+      format!("instance 1: expected {b:?}, got {c:?}")
+    );
+    assert_eq!(diff_instances(&[a, b], &[a]), "instance length: expected 2, got 1");
+  }
+}